@@ -1,7 +1,8 @@
 use std::{
     cmp::{max, min},
-    collections::HashSet,
-    ops::{Range, RangeInclusive},
+    collections::{BTreeSet, HashSet},
+    fmt,
+    ops::RangeInclusive,
 };
 
 use nom::{
@@ -15,30 +16,65 @@ struct Sensor(i32, i32);
 #[derive(Debug, Default, PartialEq, PartialOrd, Hash, Eq)]
 struct Beacon(i32, i32);
 
-impl Sensor {
-    fn line_intersections(&self, beacon: &Beacon, line: i32) -> Option<RangeInclusive<i32>> {
-        let dist = (self.0.abs_diff(beacon.0) + self.1.abs_diff(beacon.1)) as i32;
-        if (line >= self.1 && line <= self.1 + dist) || (line <= self.1 && line >= self.1 - dist) {
-            let y_diff = self.1.abs_diff(line) as i32;
-            Some((self.0 - (dist - y_diff))..=(self.0 + (dist - y_diff)))
-        } else {
-            None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
+impl Metric {
+    fn distance(self, (x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> i64 {
+        let (dx, dy) = (x0.abs_diff(x1) as i64, y0.abs_diff(y1) as i64);
+        match self {
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+            Metric::Euclidean => dx * dx + dy * dy,
         }
     }
 
-    fn line_intersections_2(
+    fn half_width(self, radius: i64, dy: i64) -> Option<i64> {
+        match self {
+            Metric::Manhattan => (dy <= radius).then_some(radius - dy),
+            Metric::Chebyshev => (dy <= radius).then_some(radius),
+            Metric::Euclidean => (dy * dy <= radius).then(|| (radius - dy * dy).isqrt()),
+        }
+    }
+}
+
+impl Sensor {
+    fn line_intersections(
         &self,
         beacon: &Beacon,
         line: i32,
-        search_space: i32,
+        metric: Metric,
     ) -> Option<RangeInclusive<i32>> {
-        let dist = (self.0.abs_diff(beacon.0) + self.1.abs_diff(beacon.1)) as i32;
-        if (line >= self.1 && line <= self.1 + dist) || (line <= self.1 && line >= self.1 - dist) {
-            let y_diff = self.1.abs_diff(line) as i32;
-            Some(max(self.0 - (dist - y_diff), 0)..=min(self.0 + (dist - y_diff), search_space))
-        } else {
-            None
-        }
+        let dy = self.1.abs_diff(line) as i64;
+        let half = metric.half_width(self.distance(beacon, metric), dy)?;
+        let x = self.0 as i64;
+        Some((x - half) as i32..=(x + half) as i32)
+    }
+
+    fn distance(&self, beacon: &Beacon, metric: Metric) -> i64 {
+        metric.distance((self.0, self.1), (beacon.0, beacon.1))
+    }
+
+    fn covers(&self, beacon: &Beacon, point: (i32, i32), metric: Metric) -> bool {
+        metric.distance((self.0, self.1), point) <= self.distance(beacon, metric)
+    }
+
+    fn perimeter(&self, beacon: &Beacon) -> ([i32; 2], [i32; 2]) {
+        let reach = self.distance(beacon, Metric::Manhattan) as i32 + 1;
+        let (sum, diff) = (self.0 + self.1, self.0 - self.1);
+        ([sum - reach, sum + reach], [diff - reach, diff + reach])
+    }
+
+    fn edges(&self, beacon: &Beacon) -> ([i32; 2], [i32; 2]) {
+        let reach = self.distance(beacon, Metric::Chebyshev) as i32 + 1;
+        (
+            [self.0 - reach, self.0 + reach],
+            [self.1 - reach, self.1 + reach],
+        )
     }
 }
 
@@ -66,19 +102,17 @@ fn parse_input(input: &str) -> IResult<&str, Vec<(Sensor, Beacon)>> {
 }
 
 fn merge_ranges(ranges: &mut [RangeInclusive<i32>], r: &RangeInclusive<i32>) -> bool {
-    for range in ranges {
-        if (range.contains(&r.start()) || range.contains(&r.end()))
-            || (r.contains(&range.start()) || r.contains(&range.end()))
-        {
-            *range = min(*r.start(), *range.start())..=max(*r.end(), *range.end());
-            return true;
+    match ranges.last_mut() {
+        Some(range) if *r.start() <= range.end().saturating_add(1) => {
+            *range = *range.start()..=max(*r.end(), *range.end());
+            true
         }
+        _ => false,
     }
-    false
 }
 
 fn fold_rages(mut ranges: Vec<RangeInclusive<i32>>) -> Vec<RangeInclusive<i32>> {
-    ranges.sort_by(|a, b| a.start().cmp(&b.start()));
+    ranges.sort_by(|a, b| a.start().cmp(b.start()));
     ranges.into_iter().fold(vec![], |mut acc, r| {
         if !merge_ranges(&mut acc, &r) {
             acc.push(r);
@@ -87,41 +121,143 @@ fn fold_rages(mut ranges: Vec<RangeInclusive<i32>>) -> Vec<RangeInclusive<i32>>
     })
 }
 
-pub fn proccess_one(input: &str, line: i32) -> usize {
+pub fn count_covered(input: &str, line: i32, metric: Metric) -> usize {
     let (_, p) = parse_input(input).unwrap();
     let ranges = p
         .iter()
-        .flat_map(|(s, b)| s.line_intersections(&b, line))
+        .flat_map(|(s, b)| s.line_intersections(b, line, metric))
         .collect::<Vec<_>>();
 
     let res = fold_rages(ranges);
 
     let on_line = p
         .iter()
-        .filter(|(_, b)| b.1 == line)
+        .filter(|(_, b)| b.1 == line && res.iter().any(|r| r.contains(&b.0)))
         .map(|(_, b)| b)
         .collect::<HashSet<_>>();
-    (res[0].start().abs() + res[0].end().abs()) as usize + 1 - on_line.len()
+    let covered = res
+        .iter()
+        .map(|r| (*r.end() as i64 - *r.start() as i64 + 1) as usize)
+        .sum::<usize>();
+    covered - on_line.len()
 }
 
-pub fn proccess_two(input: &str, search_space: i32) -> usize {
-    let (_, p) = parse_input(input).unwrap();
+pub fn proccess_one(input: &str, line: i32) -> usize {
+    count_covered(input, line, Metric::Manhattan)
+}
 
-    let (res_ranges, y_axis) = (0..=search_space)
-        .find_map(|line| {
-            let ranges = p
-                .iter()
-                .flat_map(|(s, b)| s.line_intersections_2(&b, line, search_space))
-                .collect::<Vec<_>>();
-            let res = fold_rages(ranges);
-            if res.len() > 1 {
-                Some((res, line))
-            } else {
-                None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchError {
+    NoCandidate,
+    // only the uncovered positions the search happened to test, not all of them
+    Multiple(Vec<(i32, i32)>),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::NoCandidate => write!(f, "every position in the search space is covered"),
+            SearchError::Multiple(points) => {
+                write!(
+                    f,
+                    "at least {} candidate positions, including {points:?}",
+                    points.len()
+                )
             }
-        })
-        .unwrap();
-    (res_ranges[1].start() - 1) as usize * 400_000_0 + y_axis as usize
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+fn diagonal_candidates(p: &[(Sensor, Beacon)], search_space: i32) -> Vec<(i32, i32)> {
+    let (sums, diffs): (Vec<_>, Vec<_>) = p.iter().map(|(s, b)| s.perimeter(b)).unzip();
+    let (sums, diffs) = (sums.concat(), diffs.concat());
+    let crossings = sums
+        .iter()
+        .flat_map(|a| diffs.iter().map(move |b| (a, b)))
+        .flat_map(|(a, b)| {
+            let (x, y) = ((a + b).div_euclid(2), (a - b).div_euclid(2));
+            let odd = (a - b).rem_euclid(2);
+            [(x, y), (x + odd, y), (x, y + odd), (x + odd, y + odd)]
+        });
+    let s = search_space;
+    let edges = sums
+        .iter()
+        .flat_map(|&a| [(0, a), (s, a - s), (a, 0), (a - s, s)])
+        .chain(
+            diffs
+                .iter()
+                .flat_map(|&b| [(0, -b), (s, s - b), (b, 0), (b + s, s)]),
+        );
+    let corners = [(0, 0), (0, s), (s, 0), (s, s)];
+    crossings.chain(edges).chain(corners).collect()
+}
+
+fn grid_candidates(p: &[(Sensor, Beacon)], search_space: i32) -> Vec<(i32, i32)> {
+    let (xs, ys): (Vec<_>, Vec<_>) = p.iter().map(|(s, b)| s.edges(b)).unzip();
+    let xs = xs
+        .concat()
+        .into_iter()
+        .chain([0, search_space])
+        .collect::<Vec<_>>();
+    let ys = ys.concat().into_iter().chain([0, search_space]);
+    ys.flat_map(|y| xs.iter().map(move |&x| (x, y))).collect()
+}
+
+fn scan_rows(p: &[(Sensor, Beacon)], search_space: i32, metric: Metric) -> Vec<(i32, i32)> {
+    let mut found = vec![];
+    for line in 0..=search_space {
+        let ranges = p
+            .iter()
+            .flat_map(|(s, b)| s.line_intersections(b, line, metric))
+            .collect::<Vec<_>>();
+        let end = search_space + 1;
+        let mut x = 0;
+        for r in fold_rages(ranges).into_iter().chain([end..=end]) {
+            found.extend((x..min(*r.start(), end)).take(2).map(|x| (x, line)));
+            x = max(x, r.end().saturating_add(1));
+        }
+        if found.len() > 1 {
+            break;
+        }
+    }
+    found
+}
+
+pub fn find_beacon(
+    input: &str,
+    search_space: i32,
+    metric: Metric,
+) -> Result<(i32, i32), SearchError> {
+    let (_, p) = parse_input(input).unwrap();
+    let inside =
+        |&(x, y): &(i32, i32)| (0..=search_space).contains(&x) && (0..=search_space).contains(&y);
+    let uncovered =
+        |point: &(i32, i32)| inside(point) && p.iter().all(|(s, b)| !s.covers(b, *point, metric));
+    let candidates = match metric {
+        Metric::Manhattan => diagonal_candidates(&p, search_space),
+        Metric::Chebyshev => grid_candidates(&p, search_space),
+        Metric::Euclidean => scan_rows(&p, search_space, metric),
+    };
+    let found = candidates
+        .into_iter()
+        .filter(uncovered)
+        .collect::<BTreeSet<_>>();
+    match found.len() {
+        0 => Err(SearchError::NoCandidate),
+        1 => Ok(found.into_iter().next().unwrap()),
+        _ => Err(SearchError::Multiple(found.into_iter().collect())),
+    }
+}
+
+pub fn tuning_frequency((x, y): (i32, i32), multiplier: i64) -> i64 {
+    x as i64 * multiplier + y as i64
+}
+
+pub fn proccess_two(input: &str, search_space: i32) -> usize {
+    let beacon = find_beacon(input, search_space, Metric::Manhattan).unwrap();
+    tuning_frequency(beacon, 4_000_000) as usize
 }
 
 #[cfg(test)]
@@ -137,7 +273,7 @@ mod tests {
     #[test]
     fn part_two() {
         let input = std::fs::read_to_string("./input.txt").unwrap();
-        println!("Result part two: {}", proccess_two(&input, 400_000_0));
+        println!("Result part two: {}", proccess_two(&input, 4_000_000));
     }
 
     #[test]
@@ -177,4 +313,121 @@ Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3";
         assert_eq!(proccess_two(input, 20), 56000011);
     }
+
+    fn brute_force(input: &str, search_space: i32, metric: Metric) -> Vec<(i32, i32)> {
+        let (_, p) = parse_input(input).unwrap();
+        (0..=search_space)
+            .flat_map(|y| (0..=search_space).map(move |x| (x, y)))
+            .filter(|&point| p.iter().all(|(s, b)| !s.covers(b, point, metric)))
+            .collect()
+    }
+
+    #[test]
+    fn find_beacon_test() {
+        let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3";
+        assert_eq!(find_beacon(input, 20, Metric::Manhattan), Ok((14, 11)));
+        assert_eq!(tuning_frequency((14, 11), 10), 151);
+
+        let mut seed = 0x2545f4914f6cdd1du64;
+        let mut next = move |n: i32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n as u64) as i32
+        };
+        let mut edges = 0;
+        for metric in [Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean] {
+            for _ in 0..2000 {
+                let input = (0..next(6) + 2)
+                    .map(|_| {
+                        let (x, y) = (next(13) - 1, next(13) - 1);
+                        let (bx, by) = (x + next(15) - 7, y + next(15) - 7);
+                        format!("Sensor at x={x}, y={y}: closest beacon is at x={bx}, y={by}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                match brute_force(&input, 10, metric)[..] {
+                    [] => assert_eq!(
+                        find_beacon(&input, 10, metric),
+                        Err(SearchError::NoCandidate),
+                        "{input}"
+                    ),
+                    [(x, y)] => {
+                        if [x, y].iter().any(|v| *v == 0 || *v == 10) {
+                            edges += 1;
+                        }
+                        assert_eq!(find_beacon(&input, 10, metric), Ok((x, y)), "{input}")
+                    }
+                    ref points => match find_beacon(&input, 10, metric) {
+                        Ok(point) => assert!(points.contains(&point), "{input}"),
+                        Err(SearchError::Multiple(found)) => {
+                            assert!(found.len() > 1, "{input}");
+                            assert!(found.iter().all(|p| points.contains(p)), "{input}")
+                        }
+                        Err(SearchError::NoCandidate) => panic!("{input}"),
+                    },
+                }
+            }
+        }
+        assert!(edges > 0);
+        let covered = "Sensor at x=5, y=5: closest beacon is at x=5, y=15";
+        assert_eq!(
+            find_beacon(covered, 10, Metric::Manhattan),
+            Err(SearchError::NoCandidate)
+        );
+        let open = "Sensor at x=5, y=5: closest beacon is at x=5, y=1";
+        let error = find_beacon(open, 10, Metric::Euclidean).unwrap_err();
+        assert!(matches!(error, SearchError::Multiple(_)));
+        assert!(brute_force(open, 10, Metric::Euclidean).len() > 2);
+        assert_eq!(
+            error.to_string(),
+            "at least 2 candidate positions, including [(0, 0), (1, 0)]"
+        );
+    }
+
+    #[test]
+    fn coverage_test() {
+        assert_eq!(fold_rages(vec![4..=6, 1..=3, 8..=9, 2..=2]), [1..=6, 8..=9]);
+        assert_eq!(fold_rages(vec![-5..=0, 3..=10, -2..=4]), [-5..=10]);
+
+        let input = "Sensor at x=0, y=0: closest beacon is at x=2, y=0
+Sensor at x=10, y=0: closest beacon is at x=10, y=1
+Sensor at x=20, y=0: closest beacon is at x=17, y=0";
+        assert_eq!(proccess_one(input, 0), 5 - 1 + 3 + 7 - 1);
+        assert_eq!(proccess_one(input, 1), 3 + 1 - 1 + 5);
+        assert_eq!(proccess_one(input, 5), 0);
+    }
+
+    #[test]
+    fn metrics_test() {
+        let input = "Sensor at x=0, y=0: closest beacon is at x=3, y=4";
+        assert_eq!(count_covered(input, 0, Metric::Manhattan), 15);
+        assert_eq!(count_covered(input, 0, Metric::Chebyshev), 9);
+        assert_eq!(count_covered(input, 0, Metric::Euclidean), 11);
+        assert_eq!(count_covered(input, 4, Metric::Manhattan), 6);
+        assert_eq!(count_covered(input, 4, Metric::Chebyshev), 8);
+        assert_eq!(count_covered(input, 4, Metric::Euclidean), 6);
+        assert_eq!(count_covered(input, 5, Metric::Euclidean), 1);
+        assert_eq!(count_covered(input, 6, Metric::Euclidean), 0);
+
+        let input = "Sensor at x=-6, y=4: closest beacon is at x=3, y=4
+Sensor at x=14, y=4: closest beacon is at x=5, y=4
+Sensor at x=4, y=-6: closest beacon is at x=4, y=3
+Sensor at x=4, y=14: closest beacon is at x=4, y=5";
+        assert_eq!(find_beacon(input, 8, Metric::Chebyshev), Ok((4, 4)));
+        assert!(find_beacon(input, 8, Metric::Manhattan).is_err());
+    }
 }
@@ -1,140 +1,499 @@
-#![feature(iter_array_chunks)]
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
 
-use std::collections::HashSet;
+pub type Cube = [i64; 3];
 
-fn count_sides(matrix: &Vec<Vec<Vec<bool>>>) -> usize {
-    let mut sum = 0;
-    for (i, x) in matrix.iter().enumerate() {
-        for (j, y) in x.iter().enumerate() {
-            for (k, z) in y.iter().enumerate() {
-                if *z {
-                    sum += check_sides([i, j, k], matrix);
+const SIDES: [Cube; 6] = [
+    [1, 0, 0],
+    [-1, 0, 0],
+    [0, 1, 0],
+    [0, -1, 0],
+    [0, 0, 1],
+    [0, 0, -1],
+];
+const DENSE_LIMIT: i64 = 1 << 24;
+
+fn add([x, y, z]: Cube, [dx, dy, dz]: Cube) -> Cube {
+    [x + dx, y + dy, z + dz]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: expected three comma separated 32-bit integers, found {:?}",
+            self.line, self.found
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone)]
+enum Cells {
+    Dense(Vec<bool>),
+    Sparse(HashSet<Cube>),
+}
+
+#[derive(Debug, Clone)]
+struct VoxelSet {
+    min: Cube,
+    max: Cube,
+    outside: bool,
+    cells: Cells,
+}
+
+impl VoxelSet {
+    fn new(min: Cube, max: Cube, outside: bool) -> VoxelSet {
+        let volume = (0..3).try_fold(1i64, |acc, i| acc.checked_mul(max[i] - min[i] + 1));
+        let cells = match volume {
+            Some(volume) if volume <= DENSE_LIMIT => Cells::Dense(vec![false; volume as usize]),
+            _ => Cells::Sparse(HashSet::new()),
+        };
+        VoxelSet {
+            min,
+            max,
+            outside,
+            cells,
+        }
+    }
+
+    fn in_bounds(&self, p: Cube) -> bool {
+        (0..3).all(|i| (self.min[i]..=self.max[i]).contains(&p[i]))
+    }
+
+    fn index(&self, p: Cube) -> usize {
+        let [x, y, z] = [0, 1, 2].map(|i| (p[i] - self.min[i]) as usize);
+        let [dy, dz] = [1, 2].map(|i| (self.max[i] - self.min[i] + 1) as usize);
+        (x * dy + y) * dz + z
+    }
+
+    fn contains(&self, p: Cube) -> bool {
+        if !self.in_bounds(p) {
+            return self.outside;
+        }
+        match &self.cells {
+            Cells::Dense(cells) => cells[self.index(p)],
+            Cells::Sparse(cells) => cells.contains(&p),
+        }
+    }
+
+    fn insert(&mut self, p: Cube) -> bool {
+        assert!(self.in_bounds(p), "{p:?} is outside the voxel set");
+        let index = match self.cells {
+            Cells::Dense(_) => self.index(p),
+            Cells::Sparse(_) => 0,
+        };
+        match &mut self.cells {
+            Cells::Dense(cells) => !std::mem::replace(&mut cells[index], true),
+            Cells::Sparse(cells) => cells.insert(p),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Droplet {
+    cubes: Vec<Cube>,
+    lava: VoxelSet,
+}
+
+impl Droplet {
+    // coordinates are limited to i32 so the padded bounds and extents fit in i64
+    pub fn new(cubes: impl IntoIterator<Item = Cube>) -> Droplet {
+        let mut cubes = cubes.into_iter().collect::<Vec<_>>();
+        assert!(
+            cubes.iter().flatten().all(|v| i32::try_from(*v).is_ok()),
+            "cube coordinates must fit in an i32"
+        );
+        cubes.sort();
+        cubes.dedup();
+        let axis = |i: usize| {
+            let values = cubes.iter().map(|c| c[i]);
+            (
+                values.clone().min().unwrap_or(0) - 1,
+                values.max().unwrap_or(0) + 1,
+            )
+        };
+        let [(x0, x1), (y0, y1), (z0, z1)] = [0, 1, 2].map(axis);
+        let mut lava = VoxelSet::new([x0, y0, z0], [x1, y1, z1], false);
+        cubes.iter().for_each(|&c| {
+            lava.insert(c);
+        });
+        Droplet { cubes, lava }
+    }
+
+    pub fn cubes(&self) -> &[Cube] {
+        &self.cubes
+    }
+
+    pub fn bounds(&self) -> (Cube, Cube) {
+        (self.lava.min.map(|v| v + 1), self.lava.max.map(|v| v - 1))
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.lava.cells, Cells::Sparse(_))
+    }
+
+    pub fn contains(&self, cube: Cube) -> bool {
+        self.lava.contains(cube)
+    }
+
+    fn exterior(&self) -> Exterior {
+        let axes = [0, 1, 2].map(|i| {
+            let mut values = self
+                .cubes
+                .iter()
+                .flat_map(|c| [c[i] - 1, c[i], c[i] + 1])
+                .collect::<Vec<_>>();
+            values.sort();
+            values.dedup();
+            values
+        });
+        let real = |q: Cube| [0, 1, 2].map(|i| axes[i][q[i] as usize]);
+        let max = [0, 1, 2].map(|i| axes[i].len() as i64 - 1);
+        let mut cells = VoxelSet::new([0; 3], max, true);
+        let mut q = VecDeque::new();
+        if !self.cubes.is_empty() {
+            cells.insert([0; 3]);
+            q.push_back([0; 3]);
+        }
+        while let Some(p) = q.pop_front() {
+            for next in SIDES.map(|side| add(p, side)) {
+                if cells.in_bounds(next) && !self.contains(real(next)) && cells.insert(next) {
+                    q.push_back(next);
                 }
             }
         }
+        Exterior { axes, cells }
     }
-    sum
 }
 
-fn check_air_bubbles(matrix: &Vec<Vec<Vec<bool>>>) -> usize {
-    let start = [0, 0, 0];
-    let mut q = std::collections::VecDeque::from([start]);
-    let mut visited = HashSet::from([start]);
-    let mut sum = 0;
-    while let Some(coord) = q.pop_front() {
-        sum += 6 - check_sides(coord, matrix);
-        let next = visit_sides(coord, matrix, &visited);
-        next.iter().flat_map(|a| a).for_each(|c| {
-            visited.insert(*c);
-        });
-        q.extend(next.into_iter().flat_map(|a| a))
-    }
-
-    sum
-}
-
-fn visit_sides(
-    [x, y, z]: [usize; 3],
-    matrix: &Vec<Vec<Vec<bool>>>,
-    visited: &HashSet<[usize; 3]>,
-) -> [Option<[usize; 3]>; 6] {
-    let side1 = matrix
-        .get(x + 1)
-        .map(|a| a[y][z])
-        .filter(|_| !visited.contains(&[x + 1, y, z]))
-        .filter(|b| !b)
-        .map(|_| [x + 1, y, z]);
-    let side2 = x
-        .checked_sub(1)
-        .map(|a| matrix[a][y][z])
-        .filter(|_| !visited.contains(&[x - 1, y, z]))
-        .filter(|b| !b)
-        .map(|_| [x - 1, y, z]);
-    let side3 = matrix[x]
-        .get(y + 1)
-        .map(|a| a[z])
-        .filter(|_| !visited.contains(&[x, y + 1, z]))
-        .filter(|b| !b)
-        .map(|_| [x, y + 1, z]);
-    let side4 = y
-        .checked_sub(1)
-        .map(|a| matrix[x][a][z])
-        .filter(|_| !visited.contains(&[x, y - 1, z]))
-        .filter(|b| !b)
-        .map(|_| [x, y - 1, z]);
-    let side5 = matrix[x][y]
-        .get(z + 1)
-        .filter(|_| !visited.contains(&[x, y, z + 1]))
-        .filter(|&b| !b)
-        .map(|_| [x, y, z + 1]);
-    let side6 = z
-        .checked_sub(1)
-        .map(|a| matrix[x][y][a])
-        .filter(|_| !visited.contains(&[x, y, z - 1]))
-        .filter(|b| !b)
-        .map(|_| [x, y, z - 1]);
-
-    [side1, side2, side3, side4, side5, side6]
-}
-
-fn check_sides([x, y, z]: [usize; 3], matrix: &Vec<Vec<Vec<bool>>>) -> usize {
-    let side1 = matrix
-        .get(x + 1)
-        .map(|a| a[y][z])
-        .map(|b| Into::into(!b))
-        .unwrap_or(1);
-    let side2 = x
-        .checked_sub(1)
-        .map(|a| matrix[a][y][z])
-        .map(|b| Into::into(!b))
-        .unwrap_or(1);
-    let side3 = matrix[x]
-        .get(y + 1)
-        .map(|a| a[z])
-        .map(|b| Into::into(!b))
-        .unwrap_or(1);
-    let side4 = y
-        .checked_sub(1)
-        .map(|a| matrix[x][a][z])
-        .map(|b| Into::into(!b))
-        .unwrap_or(1);
-    let side5 = matrix[x][y].get(z + 1).map(|b| Into::into(!b)).unwrap_or(1);
-    let side6 = z
-        .checked_sub(1)
-        .map(|a| matrix[x][y][a])
-        .map(|b| Into::into(!b))
-        .unwrap_or(1);
-    side1 + side2 + side3 + side4 + side5 + side6
+struct Exterior {
+    axes: [Vec<i64>; 3],
+    cells: VoxelSet,
+}
+
+impl Exterior {
+    fn contains(&self, p: Cube) -> bool {
+        let mut q = [0; 3];
+        for i in 0..3 {
+            match self.axes[i].binary_search(&p[i]) {
+                Ok(index) => q[i] = index as i64,
+                Err(_) => return true,
+            }
+        }
+        self.cells.contains(q)
+    }
+}
+
+impl FromStr for Droplet {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let cubes = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let error = || ParseError {
+                    line: i + 1,
+                    found: line.to_string(),
+                };
+                let values = line
+                    .split(',')
+                    .map(|v| v.trim().parse::<i32>().map(i64::from))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| error())?;
+                <Cube>::try_from(values).map_err(|_| error())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Droplet::new(cubes))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    All,
+    Exterior,
+}
+
+fn check_sides(cube: Cube, open: impl Fn(Cube) -> bool) -> impl Iterator<Item = Cube> {
+    SIDES.into_iter().filter(move |&side| open(add(cube, side)))
+}
+
+fn exposed_faces(droplet: &Droplet, surface: Surface) -> Vec<(Cube, Cube)> {
+    let exterior = match surface {
+        Surface::All => None,
+        Surface::Exterior => Some(droplet.exterior()),
+    };
+    let open = |p| match &exterior {
+        Some(exterior) => exterior.contains(p),
+        None => !droplet.contains(p),
+    };
+    droplet
+        .cubes
+        .iter()
+        .flat_map(|&cube| check_sides(cube, open).map(move |side| (cube, side)))
+        .collect()
+}
+
+fn count_sides(droplet: &Droplet) -> usize {
+    exposed_faces(droplet, Surface::All).len()
+}
+
+fn check_air_bubbles(droplet: &Droplet) -> usize {
+    exposed_faces(droplet, Surface::Exterior).len()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quad {
+    pub normal: Cube,
+    pub corners: [Cube; 4],
+}
+
+impl Quad {
+    fn triangles(&self) -> [[Cube; 3]; 2] {
+        let [a, b, c, d] = self.corners;
+        [[a, b, c], [a, c, d]]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mesh {
+    quads: Vec<Quad>,
+}
+
+impl Mesh {
+    fn new(faces: &[(Cube, Cube)], merge: bool) -> Mesh {
+        let mut planes: HashMap<(usize, i64, i64), HashSet<[i64; 2]>> = HashMap::new();
+        for &(cube, side) in faces {
+            let axis = side.iter().position(|&d| d != 0).unwrap();
+            let sign = side[axis];
+            let plane = cube[axis] + sign.max(0);
+            planes
+                .entry((axis, sign, plane))
+                .or_default()
+                .insert([cube[(axis + 1) % 3], cube[(axis + 2) % 3]]);
+        }
+        let mut quads = vec![];
+        for ((axis, sign, plane), mut cells) in planes.into_iter().sorted_by_key(|(key, _)| *key) {
+            let point = |u, v| {
+                let mut p = [0; 3];
+                p[axis] = plane;
+                p[(axis + 1) % 3] = u;
+                p[(axis + 2) % 3] = v;
+                p
+            };
+            let mut normal = [0; 3];
+            normal[axis] = sign;
+            for [u0, v0] in cells.iter().copied().sorted_by_key(|&[u, v]| (v, u)) {
+                if !cells.remove(&[u0, v0]) {
+                    continue;
+                }
+                let (mut u1, mut v1) = (u0 + 1, v0 + 1);
+                while merge && cells.remove(&[u1, v0]) {
+                    u1 += 1;
+                }
+                while merge && (u0..u1).all(|u| cells.contains(&[u, v1])) {
+                    (u0..u1).for_each(|u| {
+                        cells.remove(&[u, v1]);
+                    });
+                    v1 += 1;
+                }
+                let mut corners = [point(u0, v0), point(u1, v0), point(u1, v1), point(u0, v1)];
+                if sign < 0 {
+                    corners.reverse();
+                }
+                quads.push(Quad { normal, corners });
+            }
+        }
+        Mesh { quads }
+    }
+
+    pub fn quads(&self) -> &[Quad] {
+        &self.quads
+    }
+
+    pub fn to_obj(&self) -> String {
+        let mut vertices: HashMap<Cube, usize> = HashMap::new();
+        let mut out = String::new();
+        let mut faces = String::new();
+        for triangle in self.quads.iter().flat_map(Quad::triangles) {
+            let ids = triangle.map(|p| {
+                let next = vertices.len() + 1;
+                *vertices.entry(p).or_insert_with(|| {
+                    out += &format!("v {} {} {}\n", p[0], p[1], p[2]);
+                    next
+                })
+            });
+            faces += &format!("f {} {} {}\n", ids[0], ids[1], ids[2]);
+        }
+        out + &faces
+    }
+
+    pub fn to_stl(&self, name: &str) -> String {
+        let mut out = format!("solid {name}\n");
+        for quad in &self.quads {
+            let [nx, ny, nz] = quad.normal;
+            for triangle in quad.triangles() {
+                out += &format!("  facet normal {nx} {ny} {nz}\n    outer loop\n");
+                for [x, y, z] in triangle {
+                    out += &format!("      vertex {x} {y} {z}\n");
+                }
+                out += "    endloop\n  endfacet\n";
+            }
+        }
+        out + &format!("endsolid {name}\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pocket {
+    pub voxels: usize,
+    pub bounds: (Cube, Cube),
+    pub area: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub cubes: usize,
+    pub bounds: (Cube, Cube),
+    pub area: usize,
+    pub exterior: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    pub components: Vec<Component>,
+    pub area: usize,
+    pub exterior: usize,
+}
+
+impl fmt::Display for Components {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>9} | {:>5} | {:>5} | {:>8}",
+            "component", "cubes", "area", "exterior"
+        )?;
+        for (i, c) in self.components.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>9} | {:>5} | {:>5} | {:>8}",
+                i + 1,
+                c.cubes,
+                c.area,
+                c.exterior
+            )?;
+        }
+        writeln!(
+            f,
+            "{:>9} | {:>5} | {:>5} | {:>8}",
+            "total", "", self.area, self.exterior
+        )
+    }
+}
+
+impl Droplet {
+    pub fn components(&self) -> Components {
+        let exterior = self.exterior();
+        let mut visited = VoxelSet::new(self.lava.min, self.lava.max, false);
+        let mut components = vec![];
+        for &start in &self.cubes {
+            if !visited.insert(start) {
+                continue;
+            }
+            let mut component = Component {
+                cubes: 0,
+                bounds: (start, start),
+                area: 0,
+                exterior: 0,
+            };
+            let mut q = VecDeque::from([start]);
+            while let Some(p) = q.pop_front() {
+                component.cubes += 1;
+                component.bounds.0 = [0, 1, 2].map(|i| component.bounds.0[i].min(p[i]));
+                component.bounds.1 = [0, 1, 2].map(|i| component.bounds.1[i].max(p[i]));
+                for next in SIDES.map(|side| add(p, side)) {
+                    if !self.contains(next) {
+                        component.area += 1;
+                        component.exterior += exterior.contains(next) as usize;
+                    } else if visited.insert(next) {
+                        q.push_back(next);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        Components {
+            area: components.iter().map(|c| c.area).sum(),
+            exterior: components.iter().map(|c| c.exterior).sum(),
+            components,
+        }
+    }
+
+    pub fn pockets(&self) -> Vec<Pocket> {
+        let exterior = self.exterior();
+        let mut visited = VoxelSet::new(self.lava.min, self.lava.max, false);
+        let mut pockets = vec![];
+        for start in self
+            .cubes
+            .iter()
+            .flat_map(|&c| SIDES.map(|side| add(c, side)))
+        {
+            if self.contains(start) || exterior.contains(start) || !visited.insert(start) {
+                continue;
+            }
+            pockets.extend(self.flood_pocket(start, &mut visited));
+        }
+        pockets.sort_by_key(|pocket| pocket.bounds);
+        pockets
+    }
+
+    fn flood_pocket(&self, start: Cube, visited: &mut VoxelSet) -> Option<Pocket> {
+        let mut pocket = Pocket {
+            voxels: 0,
+            bounds: (start, start),
+            area: 0,
+        };
+        let mut escaped = false;
+        let mut q = VecDeque::from([start]);
+        while let Some(p) = q.pop_front() {
+            pocket.voxels += 1;
+            pocket.bounds.0 = [0, 1, 2].map(|i| pocket.bounds.0[i].min(p[i]));
+            pocket.bounds.1 = [0, 1, 2].map(|i| pocket.bounds.1[i].max(p[i]));
+            for next in SIDES.map(|side| add(p, side)) {
+                if self.contains(next) {
+                    pocket.area += 1;
+                } else if !visited.in_bounds(next) {
+                    escaped = true;
+                } else if visited.insert(next) {
+                    q.push_back(next);
+                }
+            }
+        }
+        (!escaped).then_some(pocket)
+    }
+
+    pub fn mesh(&self, surface: Surface, merge: bool) -> Mesh {
+        Mesh::new(&exposed_faces(self, surface), merge)
+    }
 }
 
 pub fn proccess_one(input: &str) -> usize {
-    let max = input
-        .lines()
-        .flat_map(|coord| coord.split(',').flat_map(|len| len.parse::<usize>()))
-        .max()
-        .unwrap();
-    let mut m = vec![vec![vec![false; max + 1]; max + 1]; max + 1];
-    input
-        .lines()
-        .flat_map(|coord| coord.split(',').flat_map(|len| len.parse::<usize>()))
-        .array_chunks()
-        .for_each(|[x, y, z]| m[x][y][z] = true);
-    let sum = count_sides(&m);
-    sum
+    count_sides(&input.parse().unwrap())
 }
+
 pub fn proccess_two(input: &str) -> usize {
-    let max = input
-        .lines()
-        .flat_map(|coord| coord.split(',').flat_map(|len| len.parse::<usize>()))
-        .max()
-        .unwrap();
-    let mut m = vec![vec![vec![false; max + 3]; max + 3]; max + 3];
-    input
-        .lines()
-        .flat_map(|coord| coord.split(',').flat_map(|len| len.parse::<usize>()))
-        .array_chunks()
-        .for_each(|[x, y, z]| m[x + 1][y + 1][z + 1] = true);
-    check_air_bubbles(&m)
+    check_air_bubbles(&input.parse().unwrap())
 }
 
 #[cfg(test)]
@@ -243,4 +602,269 @@ mod tests {
 6,3,3";
         assert_eq!(proccess_two(input), 90);
     }
+
+    #[test]
+    fn signed_and_sparse_test() {
+        let droplet = "-1,0,0\n0,0,0\n1000000,-1000000,5"
+            .parse::<Droplet>()
+            .unwrap();
+        assert!(droplet.is_sparse());
+        assert_eq!(droplet.bounds(), ([-1, -1000000, 0], [1000000, 0, 5]));
+        assert_eq!(count_sides(&droplet), 16);
+        assert_eq!(check_air_bubbles(&droplet), 16);
+
+        let shifted = "2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5"
+            .lines()
+            .map(|line| {
+                let c = line
+                    .split(',')
+                    .map(|v| v.parse::<i64>().unwrap())
+                    .collect::<Vec<_>>();
+                format!("{},{},{}", c[0] - 10, -c[1], c[2] + 3_000_000)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(proccess_one(&shifted), 64);
+        assert_eq!(proccess_two(&shifted), 58);
+
+        assert_eq!(
+            "1,2,3\n1,2\n".parse::<Droplet>().unwrap_err(),
+            ParseError {
+                line: 2,
+                found: "1,2".to_string()
+            }
+        );
+        assert!("1,2,3,4".parse::<Droplet>().is_err());
+        assert!("1,-x,3".parse::<Droplet>().is_err());
+        assert_eq!(
+            "-5000000000000000000,0,0\n5000000000000000000,0,0"
+                .parse::<Droplet>()
+                .unwrap_err()
+                .line,
+            1
+        );
+        assert!("9223372036854775807,0,0".parse::<Droplet>().is_err());
+        let wide = "-2147483648,-2147483648,-2147483648\n2147483647,2147483647,2147483647"
+            .parse::<Droplet>()
+            .unwrap();
+        assert!(wide.is_sparse());
+        assert_eq!(count_sides(&wide), 12);
+        assert_eq!(check_air_bubbles(&wide), 12);
+    }
+
+    #[test]
+    fn sparse_matches_dense() {
+        let mut seed = 0x2545f4914f6cdd1du64;
+        let mut next = move |n: i64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % n as u64) as i64
+        };
+        for _ in 0..20 {
+            let cubes = (0..300)
+                .map(|_| [next(8), next(8), next(8)])
+                .collect::<Vec<_>>();
+            let dense = Droplet::new(cubes.clone());
+            let far = cubes
+                .iter()
+                .map(|c| add(*c, [0, 0, 1 << 30]))
+                .chain([[0, 0, 0]]);
+            let sparse = Droplet::new(far);
+            assert!(!dense.is_sparse());
+            assert!(sparse.is_sparse());
+            assert_eq!(count_sides(&dense) + 6, count_sides(&sparse));
+            assert_eq!(check_air_bubbles(&dense) + 6, check_air_bubbles(&sparse));
+        }
+
+        let star = [
+            [0, 0, 0],
+            [10, 0, 0],
+            [-10, 0, 0],
+            [0, 10, 0],
+            [0, -10, 0],
+            [0, 0, 10],
+            [0, 0, -10],
+        ];
+        let dense = Droplet::new(star);
+        let sparse = Droplet::new(
+            star.into_iter()
+                .chain([[0, 1 << 30, 1 << 30], [0, -(1 << 30), -(1 << 30)]]),
+        );
+        assert!(sparse.is_sparse());
+        assert_eq!(check_air_bubbles(&dense), 42);
+        assert_eq!(check_air_bubbles(&sparse), 54);
+        assert!(sparse.pockets().is_empty());
+    }
+
+    #[test]
+    fn mesh_test() {
+        let input = "2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5";
+        let droplet = input.parse::<Droplet>().unwrap();
+        assert_eq!(droplet.mesh(Surface::All, false).quads().len(), 64);
+        assert_eq!(droplet.mesh(Surface::Exterior, false).quads().len(), 58);
+        let merged = droplet.mesh(Surface::All, true);
+        assert!(merged.quads().len() < 64);
+        let area = |q: &Quad| {
+            let [a, _, c, _] = q.corners;
+            (0..3)
+                .map(|i| (a[i] - c[i]).abs())
+                .filter(|&d| d != 0)
+                .product::<i64>()
+        };
+        assert_eq!(merged.quads().iter().map(area).sum::<i64>(), 64);
+
+        let cube = "0,0,0".parse::<Droplet>().unwrap().mesh(Surface::All, true);
+        for quad in cube.quads() {
+            let [a, b, c, _] = quad.corners;
+            let (u, v) = (
+                [0, 1, 2].map(|i| b[i] - a[i]),
+                [0, 1, 2].map(|i| c[i] - b[i]),
+            );
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            assert_eq!(cross, quad.normal);
+        }
+        let obj = cube.to_obj();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 12);
+        let stl = cube.to_stl("cube");
+        assert!(stl.starts_with("solid cube\n") && stl.ends_with("endsolid cube\n"));
+        assert_eq!(stl.matches("facet normal").count(), 12);
+
+        let bar = "0,0,0\n1,0,0\n2,0,0".parse::<Droplet>().unwrap();
+        assert_eq!(bar.mesh(Surface::All, true).quads().len(), 6);
+    }
+
+    #[test]
+    fn pockets_test() {
+        let droplet = "2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5"
+            .parse::<Droplet>()
+            .unwrap();
+        assert_eq!(
+            droplet.pockets(),
+            [Pocket {
+                voxels: 1,
+                bounds: ([2, 2, 5], [2, 2, 5]),
+                area: 6
+            }]
+        );
+
+        let mut shell = (0..5)
+            .flat_map(|x| (0..3).flat_map(move |y| (0..3).map(move |z| [x, y, z])))
+            .filter(|&[x, y, z]| !((1..4).contains(&x) && y == 1 && z == 1))
+            .collect::<Vec<_>>();
+        shell.extend([
+            [9, 9, 9],
+            [9, 10, 10],
+            [9, 11, 9],
+            [10, 10, 9],
+            [8, 10, 9],
+            [9, 10, 8],
+        ]);
+        let droplet = Droplet::new(shell);
+        let pockets = droplet.pockets();
+        assert_eq!(
+            pockets,
+            [
+                Pocket {
+                    voxels: 3,
+                    bounds: ([1, 1, 1], [3, 1, 1]),
+                    area: 14
+                },
+                Pocket {
+                    voxels: 1,
+                    bounds: ([9, 10, 9], [9, 10, 9]),
+                    area: 6
+                }
+            ]
+        );
+
+        let mut visited = VoxelSet::new(droplet.lava.min, droplet.lava.max, false);
+        visited.insert([5, 1, 1]);
+        assert_eq!(droplet.flood_pocket([5, 1, 1], &mut visited), None);
+
+        let input = std::fs::read_to_string("./input.txt").unwrap();
+        let droplet = input.parse::<Droplet>().unwrap();
+        let trapped = droplet.pockets().iter().map(|p| p.area).sum::<usize>();
+        assert_eq!(count_sides(&droplet) - trapped, check_air_bubbles(&droplet));
+    }
+
+    #[test]
+    fn components_test() {
+        let mut cubes = (0..5)
+            .flat_map(|x| (0..5).flat_map(move |y| (0..5).map(move |z| [x, y, z])))
+            .filter(|&[x, y, z]| [x, y, z].iter().any(|&v| v == 0 || v == 4))
+            .collect::<Vec<_>>();
+        cubes.extend([[2, 2, 2], [10, 10, 10], [10, 10, 11], [-3, 0, 0]]);
+        let components = Droplet::new(cubes).components();
+        let summary = components
+            .components
+            .iter()
+            .map(|c| (c.cubes, c.area, c.exterior))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [(1, 6, 6), (98, 150 + 54, 150), (1, 6, 0), (2, 10, 10)]
+        );
+        assert_eq!(components.components[1].bounds, ([0, 0, 0], [4, 4, 4]));
+        assert_eq!(components.area, 6 + 204 + 6 + 10);
+        assert_eq!(components.exterior, 166);
+        assert_eq!(
+            components
+                .to_string()
+                .lines()
+                .last()
+                .unwrap()
+                .split_whitespace()
+                .last(),
+            Some("166")
+        );
+
+        let input = std::fs::read_to_string("./input.txt").unwrap();
+        let droplet = input.parse::<Droplet>().unwrap();
+        let components = droplet.components();
+        assert_eq!(components.area, count_sides(&droplet));
+        assert_eq!(components.exterior, check_air_bubbles(&droplet));
+        println!("{components}");
+    }
 }
@@ -1,180 +1,606 @@
 use std::{
-    cmp::{max, max_by},
-    collections::{HashSet, VecDeque},
+    error::Error,
+    fmt::{self, Display},
+    ops::RangeInclusive,
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
 };
 
 use nom::{
     bytes::complete::tag,
-    character::{self, complete::line_ending},
-    combinator::{all_consuming, map},
-    multi::separated_list1,
-    sequence::{delimited, separated_pair, tuple},
+    character::{
+        self,
+        complete::{alpha1, multispace0, multispace1},
+    },
+    combinator::{all_consuming, cut},
+    multi::{many1, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
-#[derive(Debug)]
-struct Blueprint {
-    id: u8,
-    ore: u8,
-    clay: u8,
-    obsidian: (u8, u8),
-    geode: (u8, u8),
-    max_ore: u8,
-}
-
-fn parse_blueprint(input: &str) -> IResult<&str, Blueprint> {
-    let (input, id) = delimited(tag("Blueprint "), character::complete::u8, tag(": "))(input)?;
-    let (input, ore) = delimited(
-        tag("Each ore robot costs "),
-        character::complete::u8,
-        tag(" ore. "),
-    )(input)?;
-    let (input, clay) = delimited(
-        tag("Each clay robot costs "),
-        character::complete::u8,
-        tag(" ore. "),
-    )(input)?;
-    let (input, obsidian) = delimited(
-        tag("Each obsidian robot costs "),
-        separated_pair(
-            character::complete::u8,
-            tag(" ore and "),
-            character::complete::u8,
-        ),
-        tag(" clay. "),
-    )(input)?;
-    let (input, geode) = delimited(
-        tag("Each geode robot costs "),
-        separated_pair(
-            character::complete::u8,
-            tag(" ore and "),
-            character::complete::u8,
-        ),
-        tag(" obsidian."),
-    )(input)?;
-
-    Ok((
-        input,
-        Blueprint {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlueprintError {
+    Syntax { line: usize, found: String },
+    MissingRobot { blueprint: u32, resource: String },
+    DuplicateRobot { blueprint: u32, resource: String },
+}
+
+impl Display for BlueprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlueprintError::Syntax { line, found } if found.is_empty() => {
+                write!(f, "line {line}: unexpected end of line")
+            }
+            BlueprintError::Syntax { line, found } => {
+                write!(f, "line {line}: unexpected input {found:?}")
+            }
+            BlueprintError::MissingRobot {
+                blueprint,
+                resource,
+            } => write!(
+                f,
+                "blueprint {blueprint}: {resource} is used as a cost but has no {resource} robot"
+            ),
+            BlueprintError::DuplicateRobot {
+                blueprint,
+                resource,
+            } => write!(
+                f,
+                "blueprint {blueprint}: more than one {resource} robot recipe"
+            ),
+        }
+    }
+}
+
+impl Error for BlueprintError {}
+
+#[derive(Debug, Clone)]
+struct Recipe {
+    robot: usize,
+    cost: Vec<(usize, u32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Blueprint {
+    id: u32,
+    resources: Vec<String>,
+    recipes: Vec<Recipe>,
+    start: usize,
+}
+
+impl Blueprint {
+    fn new(id: u32, recipes: Vec<RawRecipe>) -> Result<Blueprint, BlueprintError> {
+        let mut resources: Vec<String> = Vec::new();
+        let mut index = |name: &str| match resources.iter().position(|r| r == name) {
+            Some(i) => i,
+            None => {
+                resources.push(name.to_string());
+                resources.len() - 1
+            }
+        };
+        let robots: Vec<_> = recipes.iter().map(|(robot, _)| index(robot)).collect();
+        let recipes: Vec<_> = robots
+            .iter()
+            .zip(recipes)
+            .map(|(&robot, (_, cost))| Recipe {
+                robot,
+                cost: cost.into_iter().map(|(n, r)| (index(r), n)).collect(),
+            })
+            .collect();
+        let name = |r: usize| resources[r].clone();
+        if let Some(r) = (0..resources.len()).find(|r| !robots.contains(r)) {
+            return Err(BlueprintError::MissingRobot {
+                blueprint: id,
+                resource: name(r),
+            });
+        }
+        if let Some(r) =
+            (0..resources.len()).find(|r| robots.iter().filter(|x| *x == r).count() > 1)
+        {
+            return Err(BlueprintError::DuplicateRobot {
+                blueprint: id,
+                resource: name(r),
+            });
+        }
+        // the puzzle starts with an ore robot; without one, the first recipe's robot
+        let start = resources
+            .iter()
+            .position(|r| r == "ore")
+            .unwrap_or(recipes[0].robot);
+        Ok(Blueprint {
             id,
-            ore,
-            clay,
-            obsidian,
-            geode,
-            max_ore: [ore, clay, obsidian.0, geode.0].into_iter().max().unwrap(),
-        },
-    ))
+            resources,
+            recipes,
+            start,
+        })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn resources(&self) -> &[String] {
+        &self.resources
+    }
+
+    pub fn resource(&self, name: &str) -> Option<usize> {
+        self.resources.iter().position(|r| r == name)
+    }
+
+    pub fn starting_robot(&self) -> usize {
+        self.start
+    }
+
+    pub fn with_starting_robot(mut self, resource: &str) -> Option<Blueprint> {
+        self.start = self.resource(resource)?;
+        Some(self)
+    }
+
+    fn recipe(&self, robot: usize) -> Option<&Recipe> {
+        self.recipes.iter().find(|r| r.robot == robot)
+    }
+
+    fn start(&self) -> State {
+        let mut robots = vec![0; self.resources.len()];
+        robots[self.start] = 1;
+        State {
+            resources: vec![0; robots.len()],
+            robots,
+            time: 0,
+        }
+    }
+
+    fn max_robots(&self, target: usize) -> Vec<u32> {
+        let mut max = vec![0; self.resources.len()];
+        for (r, n) in self.recipes.iter().flat_map(|r| &r.cost) {
+            max[*r] = max[*r].max(*n);
+        }
+        max[target] = u32::MAX;
+        max
+    }
+
+    fn name(&self, resource: usize) -> &str {
+        &self.resources[resource]
+    }
+
+    fn robot_name(&self, resource: usize) -> String {
+        match self.name(resource) {
+            "geode" => "geode-cracking".to_string(),
+            name => format!("{name}-collecting"),
+        }
+    }
+}
+
+fn parse_cost(input: &str) -> IResult<&str, (u32, &str)> {
+    separated_pair(character::complete::u32, multispace1, alpha1)(input)
+}
+
+type RawRecipe<'a> = (&'a str, Vec<(u32, &'a str)>);
+
+fn parse_recipe(input: &str) -> IResult<&str, RawRecipe<'_>> {
+    preceded(
+        pair(tag("Each"), multispace1),
+        cut(tuple((
+            terminated(
+                alpha1,
+                tuple((
+                    multispace1,
+                    tag("robot"),
+                    multispace1,
+                    tag("costs"),
+                    multispace1,
+                )),
+            ),
+            terminated(
+                separated_list1(tuple((multispace1, tag("and"), multispace1)), parse_cost),
+                tag("."),
+            ),
+        ))),
+    )(input)
+}
+
+fn parse_blueprint(input: &str) -> IResult<&str, (u32, Vec<RawRecipe<'_>>)> {
+    preceded(
+        pair(tag("Blueprint"), multispace1),
+        cut(pair(
+            terminated(character::complete::u32, tag(":")),
+            many1(preceded(multispace0, parse_recipe)),
+        )),
+    )(input)
 }
 
-fn parse_blueprints(input: &str) -> IResult<&str, Vec<Blueprint>> {
-    separated_list1(line_ending, parse_blueprint)(input)
+pub fn parse_blueprints(input: &str) -> Result<Vec<Blueprint>, BlueprintError> {
+    let blueprints = all_consuming(delimited(
+        multispace0,
+        separated_list1(multispace1, parse_blueprint),
+        multispace0,
+    ))(input);
+    match blueprints {
+        Ok((_, blueprints)) => blueprints
+            .into_iter()
+            .map(|(id, recipes)| Blueprint::new(id, recipes))
+            .collect(),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+            let offset = input.len() - e.input.len();
+            Err(BlueprintError::Syntax {
+                line: input[..offset].matches('\n').count() + 1,
+                found: e.input.lines().next().unwrap_or_default().to_string(),
+            })
+        }
+        Err(nom::Err::Incomplete(_)) => unreachable!(),
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
 struct State {
-    ore_robot: u8,
-    clay_robot: u8,
-    obsidian_robot: u8,
-    geode_robot: u8,
-    resources: Resources,
-    time: u8,
+    robots: Vec<u32>,
+    resources: Vec<u32>,
+    time: u32,
 }
 
 impl State {
-    fn tick(&mut self) {
-        self.resources.ore += self.ore_robot;
-        self.resources.clay += self.clay_robot;
-        self.resources.obsidian += self.obsidian_robot;
-        self.resources.geode += self.geode_robot;
-        self.time += 1;
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Resource {
-    Geode,
-    Obsidian,
-    Clay,
-    Ore,
-    None,
-}
-#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
-struct Resources {
-    ore: u8,
-    clay: u8,
-    obsidian: u8,
-    geode: u8,
-}
-
-fn bfs(b: &Blueprint, limit: u8) -> u8 {
-    let state = State {
-        ore_robot: 1,
-        ..Default::default()
-    };
-    let mut q = VecDeque::from([state]);
-    let mut geodes = 0;
-    let mut seen_states = HashSet::new();
-
-    while let Some(mut state) = q.pop_front() {
-        geodes = geodes.max(state.resources.geode);
-        if state.resources.geode < geodes.saturating_sub(1) || seen_states.contains(&state) {
-            continue;
-        };
+    fn tick(&mut self, minutes: u32) {
+        for (resource, robots) in self.resources.iter_mut().zip(&self.robots) {
+            *resource += robots * minutes;
+        }
+        self.time += minutes;
+    }
 
-        seen_states.insert(state);
-        if state.time == limit {
-            continue;
-        }
-
-        if state.resources.ore >= b.geode.0 && state.resources.obsidian >= b.geode.1 {
-            let mut next_state = state;
-            next_state.resources.ore -= b.geode.0;
-            next_state.resources.obsidian -= b.geode.1;
-            next_state.tick();
-            next_state.geode_robot += 1;
-            q.push_back(next_state);
-        } else {
-            if state.resources.ore >= b.ore && state.ore_robot < b.max_ore {
-                let mut next_state = state;
-                next_state.resources.ore -= b.ore;
-                next_state.tick();
-                next_state.ore_robot += 1;
-                q.push_back(next_state);
+    fn wait_for(&self, cost: &[(usize, u32)]) -> Option<u32> {
+        cost.iter().try_fold(0, |wait, &(r, cost)| {
+            match (cost > self.resources[r], self.robots[r]) {
+                (false, _) => Some(wait),
+                (true, 0) => None,
+                (true, robots) => Some(wait.max((cost - self.resources[r]).div_ceil(robots))),
             }
-            if state.resources.ore >= b.clay {
-                let mut next_state = state;
-                next_state.resources.ore -= b.clay;
-                next_state.tick();
-                next_state.clay_robot += 1;
-                q.push_back(next_state);
+        })
+    }
+
+    fn build(&self, recipe: &Recipe, limit: u32) -> Option<State> {
+        let minutes = self.wait_for(&recipe.cost)? + 1;
+        if self.time + minutes >= limit {
+            return None;
+        }
+        let mut next = self.clone();
+        next.tick(minutes);
+        next.pay(&recipe.cost);
+        next.robots[recipe.robot] += 1;
+        Some(next)
+    }
+
+    fn pay(&mut self, cost: &[(usize, u32)]) {
+        for (r, n) in cost {
+            self.resources[*r] -= n;
+        }
+    }
+
+    fn total_at(&self, target: usize, limit: u32) -> u32 {
+        self.resources[target] + self.robots[target] * (limit - self.time)
+    }
+
+    fn upper_bound(&self, target: usize, limit: u32) -> u32 {
+        let left = limit - self.time;
+        self.total_at(target, limit) + left * left.saturating_sub(1) / 2
+    }
+}
+
+struct Search<'a> {
+    b: &'a Blueprint,
+    target: usize,
+    limit: u32,
+    order: Vec<&'a Recipe>,
+    max_robots: Vec<u32>,
+}
+
+impl Search<'_> {
+    fn dfs(
+        &self,
+        state: State,
+        builds: &mut Vec<(u32, usize)>,
+        best: &mut (u32, Vec<(u32, usize)>),
+    ) {
+        if state.total_at(self.target, self.limit) > best.0 {
+            *best = (state.total_at(self.target, self.limit), builds.clone());
+        }
+        if state.upper_bound(self.target, self.limit) <= best.0 {
+            return;
+        }
+        let left = self.limit - state.time;
+        for recipe in &self.order {
+            let (r, max) = (recipe.robot, self.max_robots[recipe.robot]);
+            if r != self.target && state.robots[r] * left + state.resources[r] >= max * left {
+                continue;
             }
-            if state.resources.ore >= b.obsidian.0 && state.resources.clay >= b.obsidian.1 {
-                let mut next_state = state;
-                next_state.resources.ore -= b.obsidian.0;
-                next_state.resources.clay -= b.obsidian.1;
-                next_state.tick();
-                next_state.obsidian_robot += 1;
-                q.push_back(next_state);
+            if let Some(next) = state.build(recipe, self.limit) {
+                builds.push((next.time, recipe.robot));
+                self.dfs(next, builds, best);
+                builds.pop();
             }
-            state.tick();
-            q.push_back(state);
         }
     }
-    geodes
+}
+
+fn bfs(b: &Blueprint, limit: u32) -> u32 {
+    b.resource("geode")
+        .map_or(0, |geode| best_plan(b, geode, limit).total())
+}
+
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub minute: u32,
+    pub build: Option<usize>,
+    state: State,
+}
+
+impl Step {
+    pub fn resources(&self) -> &[u32] {
+        &self.state.resources
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Plan {
+    blueprint: Blueprint,
+    target: usize,
+    steps: Vec<Step>,
+}
+
+impl Plan {
+    fn replay(b: &Blueprint, target: usize, builds: &[(u32, usize)], limit: u32) -> Plan {
+        let mut builds = builds.iter().peekable();
+        let mut state = b.start();
+        let steps = (1..=limit)
+            .map(|minute| {
+                let build = builds.next_if(|(m, _)| *m == minute).map(|(_, r)| *r);
+                let recipe = build.and_then(|r| b.recipe(r));
+                if let Some(recipe) = recipe {
+                    state.pay(&recipe.cost);
+                }
+                state.tick(1);
+                if let Some(recipe) = recipe {
+                    state.robots[recipe.robot] += 1;
+                }
+                Step {
+                    minute,
+                    build,
+                    state: state.clone(),
+                }
+            })
+            .collect();
+        Plan {
+            blueprint: b.clone(),
+            target,
+            steps,
+        }
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn total(&self) -> u32 {
+        self.steps
+            .last()
+            .map_or(0, |s| s.state.resources[self.target])
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = &self.blueprint;
+        let s = |n: u32| if n == 1 { "" } else { "s" };
+        let mut before = b.start();
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "== Minute {} ==", step.minute)?;
+            if let Some(recipe) = step.build.and_then(|r| b.recipe(r)) {
+                let cost = recipe
+                    .cost
+                    .iter()
+                    .map(|(r, n)| format!("{n} {}", b.name(*r)))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                let robot = b.robot_name(recipe.robot);
+                let article = if robot.starts_with(['a', 'e', 'i', 'o', 'u']) {
+                    "an"
+                } else {
+                    "a"
+                };
+                writeln!(f, "Spend {cost} to start building {article} {robot} robot.")?;
+            }
+            for (r, &n) in before.robots.iter().enumerate() {
+                if n == 0 {
+                    continue;
+                }
+                let total = step.state.resources[r];
+                let verb = if n == 1 { "s" } else { "" };
+                if b.name(r) == "geode" {
+                    writeln!(
+                        f,
+                        "{n} geode-cracking robot{} crack{verb} {n} geode{}; you now have {total} open geode{}.",
+                        s(n),
+                        s(n),
+                        s(total),
+                    )?;
+                } else {
+                    writeln!(
+                        f,
+                        "{n} {} robot{} collect{verb} {n} {name}; you now have {total} {name}.",
+                        b.robot_name(r),
+                        s(n),
+                        name = b.name(r),
+                    )?;
+                }
+            }
+            if let Some(robot) = step.build {
+                writeln!(
+                    f,
+                    "The new {} robot is ready; you now have {} of them.",
+                    b.robot_name(robot),
+                    step.state.robots[robot]
+                )?;
+            }
+            before = step.state.clone();
+        }
+        Ok(())
+    }
+}
+
+pub fn best_plan(b: &Blueprint, target: usize, limit: u32) -> Plan {
+    let mut order: Vec<_> = b.recipes.iter().rev().collect();
+    order.sort_by_key(|r| r.robot != target);
+    let search = Search {
+        b,
+        target,
+        limit,
+        order,
+        max_robots: b.max_robots(target),
+    };
+    let mut best = (0, Vec::new());
+    search.dfs(b.start(), &mut Vec::new(), &mut best);
+    Plan::replay(search.b, target, &best.1, limit)
+}
+
+fn max_geodes(blueprints: &[Blueprint], limit: u32) -> Vec<u32> {
+    thread::scope(|s| {
+        let handles: Vec<_> = blueprints
+            .iter()
+            .map(|b| s.spawn(move || bfs(b, limit)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Blueprint,
+    Minutes,
+    Geodes,
+    Quality,
+    Time,
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blueprint" => Ok(Column::Blueprint),
+            "minutes" => Ok(Column::Minutes),
+            "geodes" => Ok(Column::Geodes),
+            "quality" => Ok(Column::Quality),
+            "time" => Ok(Column::Time),
+            _ => Err(format!("{s} is not a report column")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Row {
+    pub blueprint: u32,
+    pub minutes: u32,
+    pub geodes: u32,
+    pub quality: u32,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    rows: Vec<Row>,
+}
+
+impl Report {
+    pub fn new(blueprints: &[Blueprint], minutes: RangeInclusive<u32>) -> Report {
+        // searched one at a time so each row's time is that search's own cost
+        let rows = blueprints
+            .iter()
+            .flat_map(|b| minutes.clone().map(move |minutes| (b, minutes)))
+            .map(|(b, minutes)| {
+                let start = Instant::now();
+                let geodes = bfs(b, minutes);
+                Row {
+                    blueprint: b.id,
+                    minutes,
+                    geodes,
+                    quality: b.id * geodes,
+                    time: start.elapsed(),
+                }
+            })
+            .collect();
+        Report { rows }
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn sort_by(&mut self, column: Column, descending: bool) {
+        self.rows.sort_by(|a, b| {
+            let order = match column {
+                Column::Blueprint => a.blueprint.cmp(&b.blueprint),
+                Column::Minutes => a.minutes.cmp(&b.minutes),
+                Column::Geodes => a.geodes.cmp(&b.geodes),
+                Column::Quality => a.quality.cmp(&b.quality),
+                Column::Time => a.time.cmp(&b.time),
+            };
+            if descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>9} | {:>7} | {:>6} | {:>7} | {:>10}",
+            "blueprint", "minutes", "geodes", "quality", "time"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:>9} | {:>7} | {:>6} | {:>7} | {:>10}",
+                row.blueprint,
+                row.minutes,
+                row.geodes,
+                row.quality,
+                format!("{:.2?}", row.time)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn quality_report(
+    input: &str,
+    minutes: RangeInclusive<u32>,
+    sort: Column,
+    descending: bool,
+) -> Result<Report, BlueprintError> {
+    let blueprints = parse_blueprints(input)?;
+    let mut report = Report::new(&blueprints, minutes);
+    report.sort_by(sort, descending);
+    Ok(report)
 }
 
 pub fn proccess_one(input: &str) -> usize {
-    let (_, b) = parse_blueprints(input).unwrap();
-    b.into_iter()
-        .enumerate()
-        .map(|(i, b)| bfs(&b, 24) as usize * (i + 1))
+    let b = parse_blueprints(input).unwrap();
+    b.iter()
+        .zip(max_geodes(&b, 24))
+        .map(|(b, geodes)| b.id as usize * geodes as usize)
         .sum()
 }
 pub fn proccess_two(input: &str) -> usize {
-    let (_, b) = parse_blueprints(input).unwrap();
-    b.into_iter()
-        .take(3)
-        .map(|(b)| bfs(&b, 32) as usize)
+    let b = parse_blueprints(input).unwrap();
+    max_geodes(&b[..b.len().min(3)], 32)
+        .into_iter()
+        .map(|geodes| geodes as usize)
         .product()
 }
 
@@ -205,6 +631,136 @@ Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsid
     fn test_part_two() {
         let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
 Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
-        assert_eq!(proccess_two(input), 56);
+        assert_eq!(proccess_two(input), 56 * 62);
+    }
+
+    #[test]
+    fn test_best_plan() {
+        let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.";
+        let b = parse_blueprints(input).unwrap();
+        let plan = best_plan(&b[0], b[0].resource("geode").unwrap(), 24);
+        assert_eq!(plan.total(), 9);
+        assert_eq!(plan.steps().len(), 24);
+        let walkthrough = plan.to_string();
+        assert!(walkthrough.starts_with(
+            "== Minute 1 ==\n1 ore-collecting robot collects 1 ore; you now have 1 ore.\n\n== Minute 2 =="
+        ));
+        assert!(walkthrough.ends_with("you now have 9 open geodes.\n"));
+    }
+
+    #[test]
+    fn test_variant_target() {
+        let input = "Blueprint 1: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian. Each diamond robot costs 2 geode and 4 obsidian.";
+        let b = parse_blueprints(input).unwrap();
+        assert_eq!(
+            b[0].resources(),
+            ["ore", "clay", "obsidian", "geode", "diamond"]
+        );
+        assert_eq!(bfs(&b[0], 24), 12);
+        let diamond = b[0].resource("diamond").unwrap();
+        assert_eq!(best_plan(&b[0], diamond, 24).total(), 6);
+        assert_eq!(best_plan(&b[0], diamond, 32).total(), 66);
+    }
+
+    #[test]
+    fn test_starting_robot() {
+        let input = "Blueprint 1: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each diamond robot costs 2 diamond.";
+        let b = parse_blueprints(input).unwrap();
+        assert_eq!(b[0].start().robots, [1, 0, 0]);
+        let diamond = b[0].resource("diamond").unwrap();
+        assert_eq!(best_plan(&b[0], diamond, 24).total(), 0);
+
+        let input = "Blueprint 1: Each clay robot costs 2 ore. Each ore robot costs 3 clay.";
+        let b = parse_blueprints(input).unwrap();
+        assert_eq!(b[0].resources(), ["clay", "ore"]);
+        assert_eq!(b[0].start().robots, [0, 1]);
+        let b = b[0].clone().with_starting_robot("clay").unwrap();
+        assert_eq!(b.start().robots, [1, 0]);
+        assert!(b.with_starting_robot("geode").is_none());
+
+        let input = "Blueprint 1: Each clay robot costs 2 clay. Each sand robot costs 1 clay.";
+        let b = parse_blueprints(input).unwrap();
+        assert_eq!(b[0].starting_robot(), 0);
+    }
+
+    #[test]
+    fn test_long_horizon() {
+        let input = "Blueprint 1: Each ore robot costs 1 ore. Each clay robot costs 1 ore. Each obsidian robot costs 1 ore and 1 clay. Each geode robot costs 1 ore and 1 obsidian.";
+        let b = parse_blueprints(input).unwrap();
+        assert_eq!(bfs(&b[0], 40), 595);
+    }
+
+    #[test]
+    fn test_multi_line() {
+        let input = "Blueprint 1:
+  Each ore robot costs 4 ore.
+  Each clay robot costs 2 ore.
+  Each obsidian robot costs 3 ore and 14 clay.
+  Each geode robot costs 2 ore and 7 obsidian.
+
+Blueprint 2:
+  Each geode robot costs 3 ore and 12 obsidian.
+  Each obsidian robot costs 3 ore and 8 clay.
+  Each clay robot costs 3 ore.
+  Each ore robot costs 2 ore.
+";
+        assert_eq!(proccess_one(input), 33);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input =
+            "Blueprint 1: Each ore robot costs 4 ore. Each geode robot costs 2 ore and 7 obsidian.";
+        assert_eq!(
+            parse_blueprints(input).unwrap_err(),
+            BlueprintError::MissingRobot {
+                blueprint: 1,
+                resource: "obsidian".to_string()
+            }
+        );
+        let input = "Blueprint 1:\n  Each ore robot costs 4 ore.\n  Each clay robot costs 2 ore\n";
+        assert_eq!(
+            parse_blueprints(input).unwrap_err().to_string(),
+            "line 3: unexpected end of line"
+        );
+        let input =
+            "Blueprint 1: Each ore robot costs 4 ore.\nBlueprint 2: Each ore robot costs four ore.";
+        assert_eq!(
+            parse_blueprints(input).unwrap_err(),
+            BlueprintError::Syntax {
+                line: 2,
+                found: "four ore.".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_quality_report() {
+        let input = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.";
+        let mut report = quality_report(input, 20..=24, Column::Quality, true).unwrap();
+        assert_eq!(report.rows().len(), 10);
+        let best = report.rows()[0];
+        assert_eq!(
+            (best.blueprint, best.minutes, best.geodes, best.quality),
+            (2, 24, 12, 24)
+        );
+        report.sort_by("minutes".parse().unwrap(), false);
+        assert!(report
+            .rows()
+            .windows(2)
+            .all(|w| w[0].minutes <= w[1].minutes));
+        let table = report.to_string();
+        assert_eq!(table.lines().count(), 11);
+        assert!(table.starts_with("blueprint | minutes | geodes | quality |       time\n"));
+    }
+
+    #[test]
+    fn report() {
+        let input = std::fs::read_to_string("./input.txt").unwrap();
+        println!(
+            "{}",
+            quality_report(&input, 20..=24, Column::Quality, true).unwrap()
+        );
     }
 }
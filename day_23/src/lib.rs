@@ -1,6 +1,3 @@
-#![feature(iter_advance_by)]
-use std::collections::{HashMap, HashSet};
-
 use itertools::Itertools;
 use strum::{EnumIter, IntoEnumIterator};
 
@@ -12,108 +9,506 @@ enum Dirs {
     East,
 }
 
-fn check_direction(
-    (x, y): (i32, i32),
-    &[north, south, west, east]: &[bool; 4],
-    dir: Dirs,
-) -> Option<((i32, i32), (i32, i32))> {
-    match dir {
-        Dirs::North if north => Some(((x, y), (x, y - 1))),
-        Dirs::South if south => Some(((x, y), (x, y + 1))),
-        Dirs::West if west => Some(((x, y), (x - 1, y))),
-        Dirs::East if east => Some(((x, y), (x + 1, y))),
-        _ => None,
-    }
-}
-
-fn move_to<I: Iterator<Item = Dirs> + Clone>(
-    (x, y): (i32, i32),
-    set: &HashSet<(i32, i32)>,
-    dir_iter: I,
-) -> Option<((i32, i32), (i32, i32))> {
-    let north = !set.contains(&(x - 1, y - 1))
-        && !set.contains(&(x, y - 1))
-        && !set.contains(&(x + 1, y - 1));
-    let south = !set.contains(&(x - 1, y + 1))
-        && !set.contains(&(x, y + 1))
-        && !set.contains(&(x + 1, y + 1));
-    let west = !set.contains(&(x - 1, y + 1))
-        && !set.contains(&(x - 1, y))
-        && !set.contains(&(x - 1, y - 1));
-    let east = !set.contains(&(x + 1, y + 1))
-        && !set.contains(&(x + 1, y))
-        && !set.contains(&(x + 1, y - 1));
-    if north && south && west && east {
-        None
-    } else {
-        dir_iter
-            .take(4)
-            .find_map(|dir| check_direction((x, y), &[north, south, west, east], dir))
+impl Dirs {
+    fn checks(&self) -> [(i32, i32); 3] {
+        match self {
+            Dirs::North => [(-1, -1), (0, -1), (1, -1)],
+            Dirs::South => [(-1, 1), (0, 1), (1, 1)],
+            Dirs::West => [(-1, 1), (-1, 0), (-1, -1)],
+            Dirs::East => [(1, 1), (1, 0), (1, -1)],
+        }
+    }
+
+    fn step(&self) -> (i32, i32) {
+        match self {
+            Dirs::North => (0, -1),
+            Dirs::South => (0, 1),
+            Dirs::West => (-1, 0),
+            Dirs::East => (1, 0),
+        }
     }
 }
 
-pub fn process1(input: &str) -> i32 {
-    let mut set = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.char_indices()
-                .filter(|&(_, c)| c == '#')
-                .map(move |(x, _)| (x as i32, y as i32))
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    pub step: (i32, i32),
+    pub empty: Vec<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Fixed,
+    Cycle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collision {
+    CancelAll,
+    FirstInOrder,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub idle: Vec<(i32, i32)>,
+    pub proposals: Vec<Proposal>,
+    pub rotation: Rotation,
+    pub collision: Collision,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            idle: NEIGHBOURS.to_vec(),
+            proposals: Dirs::iter()
+                .map(|dir| Proposal {
+                    step: dir.step(),
+                    empty: dir.checks().to_vec(),
+                })
+                .collect(),
+            rotation: Rotation::Cycle,
+            collision: Collision::CancelAll,
+        }
+    }
+}
+
+impl Rules {
+    fn order(&self, round: usize) -> Vec<&Proposal> {
+        let skip = match self.rotation {
+            Rotation::Fixed => 0,
+            Rotation::Cycle => round % self.proposals.len().max(1),
+        };
+        self.proposals
+            .iter()
+            .cycle()
+            .skip(skip)
+            .take(self.proposals.len())
+            .collect()
+    }
+
+    fn reach(&self) -> i32 {
+        self.proposals
+            .iter()
+            .flat_map(|p| p.empty.iter().chain([&p.step]))
+            .chain(&self.idle)
+            .map(|(x, y)| x.abs().max(y.abs()))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+const PADDING: i32 = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Moves {
+    proposals: usize,
+    movers: usize,
+    conflicts: usize,
+}
+
+fn count(words: &[u64]) -> usize {
+    words.iter().map(|w| w.count_ones() as usize).sum()
+}
+
+fn shift_bits(row: &[u64], d: i32) -> Vec<u64> {
+    let (words, bits) = (d.unsigned_abs() as usize / 64, d.unsigned_abs() % 64);
+    let get = |i: Option<usize>| i.and_then(|i| row.get(i)).copied().unwrap_or(0);
+    (0..row.len())
+        .map(|i| {
+            if d >= 0 {
+                let lo = get(i.checked_sub(words)) << bits;
+                let hi = match bits {
+                    0 => 0,
+                    _ => get(i.checked_sub(words + 1)) >> (64 - bits),
+                };
+                lo | hi
+            } else {
+                let hi = get(Some(i + words)) >> bits;
+                let lo = match bits {
+                    0 => 0,
+                    _ => get(Some(i + words + 1)) << (64 - bits),
+                };
+                lo | hi
+            }
         })
-        .collect::<HashSet<_>>();
-    let mut dir_iter = Dirs::iter().cycle();
-    for _ in 1..=10 {
-        set.iter()
-            .filter_map(|&coord| move_to(coord, &set, dir_iter.clone()))
-            .fold(HashMap::new(), |mut map, (old, new)| {
-                map.entry(new)
-                    .and_modify(|(_, e)| {
-                        *e += 1;
-                    })
-                    .or_insert((old, 1));
-                map
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Grid {
+    x0: i32,
+    y0: i32,
+    words: usize,
+    rows: Vec<Vec<u64>>,
+}
+
+impl Grid {
+    fn parse(input: &str) -> Grid {
+        let coords = input
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.char_indices()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(x, _)| (x as i32, y as i32))
             })
-            .into_iter()
-            .filter(|&(_, (_, count))| count == 1)
-            .for_each(|(new, (old, _))| {
-                set.remove(&old);
-                set.insert(new);
-            });
-        dir_iter.advance_by(1).unwrap();
-    }
-    let (min_y, max_y) = set
-        .iter()
-        .map(|coord| coord.1)
-        .minmax_by(|a, b| a.cmp(&b))
-        .into_option()
-        .unwrap();
-    let (min_x, max_x) = set
-        .iter()
-        .map(|coord| coord.0)
-        .minmax_by(|a, b| a.cmp(&b))
-        .into_option()
-        .unwrap();
-    (max_y + 1 - min_y) * (max_x + 1 - min_x) - set.len() as i32
+            .collect::<Vec<_>>();
+        Grid::from_coords(&coords, PADDING)
+    }
+
+    fn from_coords(coords: &[(i32, i32)], padding: i32) -> Grid {
+        let (min_x, max_x) = coords
+            .iter()
+            .map(|c| c.0)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let (min_y, max_y) = coords
+            .iter()
+            .map(|c| c.1)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let (x0, y0) = (min_x - padding, min_y - padding);
+        let words = ((max_x - x0 + padding) as usize).div_ceil(64);
+        let mut grid = Grid {
+            x0,
+            y0,
+            words,
+            rows: vec![vec![0; words]; (max_y - y0 + padding) as usize],
+        };
+        for &(x, y) in coords {
+            let (x, y) = ((x - x0) as usize, (y - y0) as usize);
+            grid.rows[y][x / 64] |= 1 << (x % 64);
+        }
+        grid
+    }
+
+    fn coords(&self) -> Vec<(i32, i32)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter().enumerate().flat_map(move |(i, &word)| {
+                    (0..64)
+                        .filter(move |bit| word & (1 << bit) != 0)
+                        .map(move |bit| (i as i32 * 64 + bit, y as i32))
+                })
+            })
+            .map(|(x, y)| (x + self.x0, y + self.y0))
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.rows.iter().map(|row| count(row)).sum()
+    }
+
+    fn bounds(&self) -> Option<((i32, i32), (i32, i32))> {
+        let (min_y, max_y) = self
+            .rows
+            .iter()
+            .positions(|row| row.iter().any(|w| *w != 0))
+            .minmax()
+            .into_option()?;
+        let columns = self.rows.iter().fold(vec![0; self.words], |acc, row| {
+            acc.iter().zip(row).map(|(a, b)| a | b).collect()
+        });
+        let min_x = columns
+            .iter()
+            .position(|w| *w != 0)
+            .map(|i| i * 64 + columns[i].trailing_zeros() as usize)?;
+        let max_x = columns
+            .iter()
+            .rposition(|w| *w != 0)
+            .map(|i| i * 64 + 63 - columns[i].leading_zeros() as usize)?;
+        Some((
+            (min_x as i32 + self.x0, min_y as i32 + self.y0),
+            (max_x as i32 + self.x0, max_y as i32 + self.y0),
+        ))
+    }
+
+    fn ensure_margin(&mut self, margin: i32) {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return;
+        };
+        let width = self.words as i32 * 64;
+        if min_x - self.x0 < margin
+            || min_y - self.y0 < margin
+            || self.x0 + width - 1 - max_x < margin
+            || self.y0 + self.rows.len() as i32 - 1 - max_y < margin
+        {
+            *self = Grid::from_coords(&self.coords(), PADDING.max(2 * margin));
+        }
+    }
+
+    fn occupied(&self, y: usize, offsets: &[(i32, i32)]) -> Vec<u64> {
+        let empty = vec![0; self.words];
+        offsets.iter().fold(vec![0; self.words], |acc, &(dx, dy)| {
+            let row = (y as i32 + dy)
+                .try_into()
+                .ok()
+                .and_then(|y: usize| self.rows.get(y))
+                .unwrap_or(&empty);
+            acc.iter()
+                .zip(shift_bits(row, -dx))
+                .map(|(a, b)| a | b)
+                .collect()
+        })
+    }
+
+    fn round(&mut self, rules: &Rules, round: usize) -> Moves {
+        let order = rules.order(round);
+        self.ensure_margin(rules.reach() + 1);
+        let height = self.rows.len();
+        let mut targets = vec![vec![vec![0; self.words]; height]; order.len()];
+        let mut moves = Moves::default();
+        for y in 0..height {
+            if self.rows[y].iter().all(|w| *w == 0) {
+                continue;
+            }
+            let crowded = self.occupied(y, &rules.idle);
+            let mut remaining: Vec<u64> = self.rows[y]
+                .iter()
+                .zip(&crowded)
+                .map(|(elf, c)| elf & c)
+                .collect();
+            for (dir, targets) in order.iter().zip(&mut targets) {
+                let blocked = self.occupied(y, &[&dir.empty[..], &[dir.step]].concat());
+                let proposed: Vec<u64> = remaining
+                    .iter()
+                    .zip(&blocked)
+                    .map(|(r, b)| r & !b)
+                    .collect();
+                remaining
+                    .iter_mut()
+                    .zip(&proposed)
+                    .for_each(|(r, p)| *r &= !p);
+                moves.proposals += count(&proposed);
+                let (dx, dy) = dir.step;
+                targets[(y as i32 + dy) as usize] = shift_bits(&proposed, dx);
+            }
+        }
+        for y in 0..height {
+            let mut once = vec![0; self.words];
+            let mut twice = vec![0; self.words];
+            for targets in &targets {
+                for ((o, t), target) in once.iter_mut().zip(&mut twice).zip(&targets[y]) {
+                    *t |= *o & *target;
+                    *o |= *target;
+                }
+            }
+            moves.conflicts += count(&twice);
+            let mut taken = vec![0; self.words];
+            for targets in &mut targets {
+                for ((t, c), target) in taken.iter_mut().zip(&twice).zip(&mut targets[y]) {
+                    *target &= match rules.collision {
+                        Collision::CancelAll => !c,
+                        Collision::FirstInOrder => !*t,
+                    };
+                    *t |= *target;
+                }
+            }
+        }
+        for (dir, targets) in order.iter().zip(&targets) {
+            let (dx, dy) = dir.step;
+            for (y, target) in targets.iter().enumerate() {
+                if target.iter().all(|w| *w == 0) {
+                    continue;
+                }
+                let from = (y as i32 - dy) as usize;
+                let source = shift_bits(target, -dx);
+                self.rows[from]
+                    .iter_mut()
+                    .zip(&source)
+                    .for_each(|(r, s)| *r &= !s);
+            }
+        }
+        for (y, target) in targets
+            .iter()
+            .flat_map(|targets| targets.iter().enumerate())
+        {
+            moves.movers += count(target);
+            self.rows[y]
+                .iter_mut()
+                .zip(target)
+                .for_each(|(r, t)| *r |= t);
+        }
+        moves
+    }
 }
 
-pub fn process2(input: &str) -> i32 {
-    let mut set = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.char_indices()
-                .filter(|&(_, c)| c == '#')
-                .map(move |(x, _)| (x as i32, y as i32))
+impl std::fmt::Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return Ok(());
+        };
+        for y in min_y..=max_y {
+            let row = &self.rows[(y - self.y0) as usize];
+            for x in min_x..=max_x {
+                let x = (x - self.x0) as usize;
+                let c = if row[x / 64] & (1 << (x % 64)) != 0 {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn empty_ground(grid: &Grid) -> i32 {
+    let ((min_x, min_y), (max_x, max_y)) = grid.bounds().unwrap();
+    (max_y + 1 - min_y) * (max_x + 1 - min_x) - grid.len() as i32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub round: usize,
+    pub bounds: ((i32, i32), (i32, i32)),
+    pub empty: i32,
+    pub proposals: usize,
+    pub movers: usize,
+    pub conflicts: usize,
+}
+
+pub struct Simulation<'a> {
+    grid: Grid,
+    rules: &'a Rules,
+    round: usize,
+}
+
+impl<'a> Simulation<'a> {
+    pub fn new(input: &str, rules: &'a Rules) -> Self {
+        Simulation {
+            grid: Grid::parse(input),
+            rules,
+            round: 0,
+        }
+    }
+
+    pub fn frame(&self) -> String {
+        let title = match self.round {
+            0 => "Initial State".to_string(),
+            round => format!("End of Round {round}"),
+        };
+        format!("== {title} ==\n{}", self.grid)
+    }
+
+    pub fn empty_ground(&self) -> i32 {
+        empty_ground(&self.grid)
+    }
+}
+
+impl Iterator for Simulation<'_> {
+    type Item = Stats;
+
+    fn next(&mut self) -> Option<Stats> {
+        let moves = self.grid.round(self.rules, self.round);
+        self.round += 1;
+        Some(Stats {
+            round: self.round,
+            bounds: self.grid.bounds()?,
+            empty: empty_ground(&self.grid),
+            proposals: moves.proposals,
+            movers: moves.movers,
+            conflicts: moves.conflicts,
         })
-        .collect::<HashSet<_>>();
+    }
+}
 
-    let mut dir_iter = Dirs::iter().cycle();
+pub fn empty_after(input: &str, rules: &Rules, rounds: usize) -> i32 {
+    let mut simulation = Simulation::new(input, rules);
+    simulation.by_ref().take(rounds).for_each(drop);
+    simulation.empty_ground()
+}
+
+pub fn settle(input: &str, rules: &Rules, limit: usize) -> Option<usize> {
+    Simulation::new(input, rules)
+        .take(limit)
+        .find(|stats| stats.movers == 0)
+        .map(|stats| stats.round)
+}
 
-    for round in 1.. {
+pub fn frames(input: &str, rules: &Rules, rounds: usize) -> String {
+    let mut simulation = Simulation::new(input, rules);
+    let mut frames = vec![simulation.frame()];
+    for _ in 0..rounds {
+        simulation.next();
+        frames.push(simulation.frame());
+    }
+    frames.join("\n")
+}
+
+pub fn process1(input: &str) -> i32 {
+    empty_after(input, &Rules::default(), 10)
+}
+
+pub fn process2(input: &str) -> i32 {
+    settle(input, &Rules::default(), usize::MAX).unwrap() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    fn check_direction(
+        (x, y): (i32, i32),
+        &[north, south, west, east]: &[bool; 4],
+        dir: Dirs,
+    ) -> Option<((i32, i32), (i32, i32))> {
+        match dir {
+            Dirs::North if north => Some(((x, y), (x, y - 1))),
+            Dirs::South if south => Some(((x, y), (x, y + 1))),
+            Dirs::West if west => Some(((x, y), (x - 1, y))),
+            Dirs::East if east => Some(((x, y), (x + 1, y))),
+            _ => None,
+        }
+    }
+
+    fn move_to<I: Iterator<Item = Dirs> + Clone>(
+        (x, y): (i32, i32),
+        set: &HashSet<(i32, i32)>,
+        dir_iter: I,
+    ) -> Option<((i32, i32), (i32, i32))> {
+        let north = !set.contains(&(x - 1, y - 1))
+            && !set.contains(&(x, y - 1))
+            && !set.contains(&(x + 1, y - 1));
+        let south = !set.contains(&(x - 1, y + 1))
+            && !set.contains(&(x, y + 1))
+            && !set.contains(&(x + 1, y + 1));
+        let west = !set.contains(&(x - 1, y + 1))
+            && !set.contains(&(x - 1, y))
+            && !set.contains(&(x - 1, y - 1));
+        let east = !set.contains(&(x + 1, y + 1))
+            && !set.contains(&(x + 1, y))
+            && !set.contains(&(x + 1, y - 1));
+        if north && south && west && east {
+            None
+        } else {
+            dir_iter
+                .take(4)
+                .find_map(|dir| check_direction((x, y), &[north, south, west, east], dir))
+        }
+    }
+
+    fn reference_round(set: &mut HashSet<(i32, i32)>, round: usize) -> usize {
         let changes = set
             .iter()
-            .filter_map(|&coord| move_to(coord, &set, dir_iter.clone()))
+            .filter_map(|&coord| move_to(coord, set, Dirs::iter().cycle().skip(round % 4)))
             .fold(HashMap::new(), |mut map, (old, new)| {
                 map.entry(new)
                     .and_modify(|(_, e)| {
@@ -122,10 +517,7 @@ pub fn process2(input: &str) -> i32 {
                     .or_insert((old, 1));
                 map
             });
-
-        if changes.len() == 0 {
-            return round;
-        }
+        let proposals = changes.values().map(|(_, count)| count).sum();
         changes
             .into_iter()
             .filter(|&(_, (_, count))| count == 1)
@@ -133,14 +525,37 @@ pub fn process2(input: &str) -> i32 {
                 set.remove(&old);
                 set.insert(new);
             });
-        dir_iter.advance_by(1).unwrap();
+        proposals
     }
-    unreachable!()
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn bitset_matches_reference() {
+        for input in [
+            std::fs::read_to_string("./input.txt").unwrap(),
+            ".....\n..##.\n..#..\n.....\n..##.\n.....".to_string(),
+        ] {
+            let mut grid = Grid::parse(&input);
+            let mut set: HashSet<_> = grid.coords().into_iter().collect();
+            for round in 0..60 {
+                assert_eq!(
+                    grid.round(&Rules::default(), round).proposals,
+                    reference_round(&mut set, round)
+                );
+                let mut coords = grid.coords();
+                coords.sort();
+                assert_eq!(coords, set.iter().copied().sorted().collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn shift_bits_test() {
+        let row = [1 << 63, 1];
+        assert_eq!(shift_bits(&row, 1), [0, 0b11]);
+        assert_eq!(shift_bits(&row, -1), [0b11 << 62, 0]);
+        assert_eq!(shift_bits(&row, 64), [0, 1 << 63]);
+        assert_eq!(shift_bits(&row, -64), [1, 0]);
+    }
 
     #[test]
     fn process_one() {
@@ -181,4 +596,80 @@ mod tests {
         let result = process2(input);
         assert_eq!(result, 20)
     }
+
+    #[test]
+    fn rules_test() {
+        let input = ".....\n..##.\n..#..\n.....\n..##.\n.....";
+        let first_wins = Rules {
+            collision: Collision::FirstInOrder,
+            ..Rules::default()
+        };
+        let mut grid = Grid::parse(input);
+        grid.round(&first_wins, 0);
+        assert_eq!(
+            grid.coords().into_iter().sorted().collect::<Vec<_>>(),
+            [(2, 0), (2, 2), (2, 3), (3, 0), (3, 3)]
+        );
+
+        let fixed = Rules {
+            rotation: Rotation::Fixed,
+            ..Rules::default()
+        };
+        let (mut a, mut b) = (Grid::parse(input), Grid::parse(input));
+        a.round(&fixed, 0);
+        b.round(&Rules::default(), 0);
+        assert_eq!(a.coords(), b.coords());
+        a.round(&fixed, 1);
+        b.round(&Rules::default(), 1);
+        assert_ne!(a.coords(), b.coords());
+
+        let long_jumps = Rules {
+            proposals: Rules::default()
+                .proposals
+                .into_iter()
+                .map(|p| Proposal {
+                    step: (p.step.0 * 2, p.step.1 * 2),
+                    ..p
+                })
+                .collect(),
+            ..Rules::default()
+        };
+        let mut grid = Grid::parse(input);
+        grid.round(&long_jumps, 0);
+        assert_eq!(grid.len(), 5);
+        assert!(grid.coords().contains(&(2, -1)));
+        let mut grid = Grid::parse("#..\n...\n#..\n.#.");
+        grid.round(&long_jumps, 0);
+        assert_eq!(grid.len(), 3);
+        assert_eq!(settle(input, &Rules::default(), 10), Some(4));
+    }
+
+    #[test]
+    fn stats_test() {
+        let input = ".....\n..##.\n..#..\n.....\n..##.\n.....";
+        let rules = Rules::default();
+        let stats = Simulation::new(input, &rules).take(4).collect::<Vec<_>>();
+        assert_eq!(
+            stats[0],
+            Stats {
+                round: 1,
+                bounds: ((2, 0), (3, 4)),
+                empty: 5,
+                proposals: 5,
+                movers: 3,
+                conflicts: 1,
+            }
+        );
+        assert_eq!(stats[3].movers, 0);
+        let first_wins = Rules {
+            collision: Collision::FirstInOrder,
+            ..Rules::default()
+        };
+        let first = Simulation::new(input, &first_wins).next().unwrap();
+        assert_eq!((first.proposals, first.movers, first.conflicts), (5, 4, 1));
+        assert_eq!(
+            frames(input, &rules, 1),
+            "== Initial State ==\n##\n#.\n..\n##\n\n== End of Round 1 ==\n##\n..\n#.\n.#\n#.\n"
+        );
+    }
 }
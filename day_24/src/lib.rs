@@ -1,291 +1,612 @@
 use std::{
-    collections::HashSet,
-    fmt::{write, Display},
+    collections::{HashMap, VecDeque},
+    fmt::Display,
 };
 
-#[derive(Debug)]
-struct Field {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
     field: Vec<Vec<FieldType>>,
     start: (usize, usize),
     end: (usize, usize),
     width: usize,
 }
 
+/// Renders in the puzzle's format, so stacked blizzards show only their count as a digit
+/// (capped at 9) and can't be read back by [`try_parse`]. Rocks always show as `O`, hiding
+/// any blizzards crossing them.
 impl Display for Field {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in &self.field {
-            for t in line {
-                match t {
-                    FieldType::Wall => write!(f, "#,"),
-                    FieldType::Blizzards(a) if a.iter().all(|a| a.is_none()) => write!(f, ".,"),
-                    FieldType::Blizzards([left, right, up, down]) => write!(f, "B,"),
+        self.render(f, None)
+    }
+}
+
+impl Field {
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    pub fn end(&self) -> (usize, usize) {
+        self.end
+    }
+
+    fn render(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        expedition: Option<(usize, usize)>,
+    ) -> std::fmt::Result {
+        for (y, line) in self.field.iter().enumerate() {
+            for (x, t) in line.iter().enumerate() {
+                let c = match t {
+                    _ if expedition == Some((x, y)) => 'E',
+                    FieldType::Wall => '#',
+                    FieldType::Rock(_) => 'O',
+                    FieldType::Blizzards(counts) => match counts.iter().sum::<u8>() {
+                        0 => '.',
+                        1 => Blizzard::ALL[counts.iter().position(|n| *n == 1).unwrap()].glyph(),
+                        n => char::from_digit(n.min(9) as u32, 10).unwrap(),
+                    },
                 };
+                write!(f, "{c}")?;
             }
-            writeln!(f, "");
+            writeln!(f)?;
         }
         Ok(())
     }
-}
 
-impl Field {
-    fn tick(&mut self) {
-        let mut new_field = Vec::with_capacity(self.field.len());
-        new_field.extend((0..self.field.len()).map(|_| Vec::with_capacity(self.width)));
-        new_field
-            .first_mut()
-            .map(|a| *a = self.field.first().unwrap().clone());
-        for y in 1..self.field.len() - 1 {
-            new_field[y].push(FieldType::Wall);
-            for x in 1..self.width - 1 {
-                new_field[y].push(FieldType::Blizzards(self.find((x, y))));
+    pub fn tick(&mut self) {
+        let mut new_field = self.field.clone();
+        for (y, line) in new_field
+            .iter_mut()
+            .enumerate()
+            .take(self.field.len() - 1)
+            .skip(1)
+        {
+            for (x, t) in line.iter_mut().enumerate().take(self.width - 1).skip(1) {
+                *t = match t {
+                    FieldType::Wall => FieldType::Wall,
+                    FieldType::Rock(_) => FieldType::Rock(self.find((x, y))),
+                    FieldType::Blizzards(_) => FieldType::Blizzards(self.find((x, y))),
+                };
             }
-            new_field[y].push(FieldType::Wall);
         }
-        new_field
-            .last_mut()
-            .map(|a| *a = self.field.last().unwrap().clone());
         self.field = new_field
     }
 
-    fn find(&self, (x, y): (usize, usize)) -> [Option<Blizzard>; 4] {
-        let left = if x == self.width.saturating_sub(2) {
-            match self.field[y][1] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Left))
-                    .copied(),
-            }
-        } else {
-            match self.field[y][x + 1] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Left))
-                    .copied(),
-            }
-        };
-        let right = if x == 1 {
-            match self.field[y][self.width.saturating_sub(2)] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Right))
-                    .copied(),
-            }
-        } else {
-            match self.field[y][x - 1] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Right))
-                    .copied(),
+    fn find(&self, (x, y): (usize, usize)) -> [u8; 8] {
+        let (w, h) = (self.width as isize - 2, self.field.len() as isize - 2);
+        let mut counts = [0; 8];
+        for (i, b) in Blizzard::ALL.iter().enumerate() {
+            let (dx, dy) = b.delta();
+            let from_x = 1 + (x as isize - 1 - dx).rem_euclid(w) as usize;
+            let from_y = 1 + (y as isize - 1 - dy).rem_euclid(h) as usize;
+            counts[i] = self.field[from_y][from_x].counts()[i];
+        }
+        counts
+    }
+
+    pub fn add_blizzard(&mut self, (x, y): (usize, usize), blizzard: Blizzard) {
+        let i = Blizzard::ALL.iter().position(|b| *b == blizzard).unwrap();
+        match &mut self.field[y][x] {
+            FieldType::Blizzards(counts) | FieldType::Rock(counts) => counts[i] += 1,
+            FieldType::Wall => panic!("({x}, {y}) is a wall"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Bitset {
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl Bitset {
+    fn new(width: usize, height: usize) -> Bitset {
+        let stride = width.div_ceil(64);
+        Bitset {
+            stride,
+            words: vec![0; stride * height],
+        }
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> (usize, u64) {
+        (y * self.stride + x / 64, 1 << (x % 64))
+    }
+
+    fn contains(&self, pos: (usize, usize)) -> bool {
+        let (i, bit) = self.index(pos);
+        self.words[i] & bit != 0
+    }
+
+    fn insert(&mut self, pos: (usize, usize)) -> bool {
+        let (i, bit) = self.index(pos);
+        let new = self.words[i] & bit == 0;
+        self.words[i] |= bit;
+        new
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+type Parents = HashMap<((usize, usize), usize), (usize, usize)>;
+
+#[derive(Debug, Clone)]
+pub struct Valley {
+    width: usize,
+    height: usize,
+    blocked: Vec<Bitset>,
+}
+
+impl Valley {
+    pub fn new(field: &Field) -> Valley {
+        let (width, height) = (field.width, field.field.len());
+        let (w, h) = (width - 2, height - 2);
+        let period = w / gcd(w, h) * h;
+        let mut walls = Bitset::new(width, height);
+        let mut blizzards = Vec::new();
+        for (y, line) in field.field.iter().enumerate() {
+            for (x, t) in line.iter().enumerate() {
+                match t {
+                    FieldType::Wall => {
+                        walls.insert((x, y));
+                    }
+                    FieldType::Rock(counts) | FieldType::Blizzards(counts) => {
+                        if matches!(t, FieldType::Rock(_)) {
+                            walls.insert((x, y));
+                        }
+                        blizzards.extend(
+                            Blizzard::ALL
+                                .iter()
+                                .zip(counts)
+                                .filter(|(_, n)| **n > 0)
+                                .map(|(b, _)| (x, y, *b)),
+                        )
+                    }
+                }
             }
-        };
-        let up = if y == self.field.len().saturating_sub(2) {
-            match self.field[1][x] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Up))
-                    .copied(),
+        }
+        let blocked = (0..period)
+            .map(|t| {
+                let mut blocked = walls.clone();
+                for &(x, y, b) in &blizzards {
+                    let (dx, dy) = b.delta();
+                    let x = 1 + (x as isize - 1 + dx * t as isize).rem_euclid(w as isize) as usize;
+                    let y = 1 + (y as isize - 1 + dy * t as isize).rem_euclid(h as isize) as usize;
+                    blocked.insert((x, y));
+                }
+                blocked
+            })
+            .collect();
+        Valley {
+            width,
+            height,
+            blocked,
+        }
+    }
+
+    fn period(&self) -> usize {
+        self.blocked.len()
+    }
+
+    fn is_open(&self, pos: (usize, usize), time: usize) -> bool {
+        !self.blocked[time % self.period()].contains(pos)
+    }
+
+    fn new_pos(
+        &self,
+        (x, y): (usize, usize),
+        time: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        [
+            Some((x, y)),
+            x.checked_sub(1).map(|x| (x, y)),
+            Some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            Some((x, y + 1)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(move |&(x, y)| x < self.width && y < self.height && self.is_open((x, y), time + 1))
+    }
+
+    fn search(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        depart: usize,
+        mut parents: Option<&mut Parents>,
+    ) -> Option<usize> {
+        if !self.is_open(from, depart) {
+            return None;
+        }
+        let mut seen = vec![Bitset::new(self.width, self.height); self.period()];
+        seen[depart % self.period()].insert(from);
+        let mut q = VecDeque::from([(from, depart)]);
+        while let Some((pos, time)) = q.pop_front() {
+            if pos == to {
+                return Some(time);
             }
-        } else {
-            match self.field[y + 1][x] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Up))
-                    .copied(),
+            for next in self.new_pos(pos, time) {
+                if seen[(time + 1) % self.period()].insert(next) {
+                    if let Some(parents) = parents.as_deref_mut() {
+                        parents.insert((next, time + 1), pos);
+                    }
+                    q.push_back((next, time + 1));
+                }
             }
-        };
+        }
+        None
+    }
 
-        let down = if y == 1 {
-            match self.field[self.field.len().saturating_sub(2)][x] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Down))
-                    .copied(),
+    pub fn earliest(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        depart: usize,
+    ) -> Option<usize> {
+        self.search(from, to, depart, None)
+    }
+
+    fn route(&self, from: (usize, usize), to: (usize, usize), depart: usize) -> Option<Vec<Move>> {
+        let mut parents = HashMap::new();
+        let arrival = self.search(from, to, depart, Some(&mut parents))?;
+        let mut moves = Vec::with_capacity(arrival - depart);
+        let mut pos = to;
+        for time in (depart + 1..=arrival).rev() {
+            let prev = parents[&(pos, time)];
+            moves.push(Move::between(prev, pos));
+            pos = prev;
+        }
+        moves.reverse();
+        Some(moves)
+    }
+
+    pub fn itinerary(
+        &self,
+        from: (usize, usize),
+        waypoints: &[(usize, usize)],
+        depart: usize,
+    ) -> Option<Vec<usize>> {
+        let mut pos = from;
+        let mut time = depart;
+        waypoints
+            .iter()
+            .map(|&to| {
+                time = self.earliest(pos, to, time)?;
+                pos = to;
+                Some(time)
+            })
+            .collect()
+    }
+
+    pub fn arrivals(&self, to: (usize, usize)) -> Arrivals {
+        let period = self.period();
+        let index = |(x, y): (usize, usize)| y * self.width + x;
+        let mut steps = vec![vec![None; self.width * self.height]; period];
+        let mut q = VecDeque::new();
+        for (phase, steps) in steps.iter_mut().enumerate() {
+            if self.is_open(to, phase) {
+                steps[index(to)] = Some(0);
+                q.push_back((to, phase));
             }
-        } else {
-            match self.field[y - 1][x] {
-                FieldType::Wall => None,
-                FieldType::Blizzards(a) => a
-                    .iter()
-                    .flatten()
-                    .find(|a| matches!(a, Blizzard::Down))
-                    .copied(),
+        }
+        while let Some((pos, phase)) = q.pop_front() {
+            let n: u32 = steps[phase][index(pos)].unwrap();
+            let prev_phase = (phase + period - 1) % period;
+            // new_pos checks the minute after the one given, so this yields the cells open at prev_phase
+            for prev in self.new_pos(pos, prev_phase + period - 1) {
+                let slot = &mut steps[prev_phase][index(prev)];
+                if slot.is_none() {
+                    *slot = Some(n + 1);
+                    q.push_back((prev, prev_phase));
+                }
             }
-        };
-        [left, right, up, down]
+        }
+        Arrivals {
+            width: self.width,
+            steps,
+        }
     }
-    fn new_pos(&self, (x, y): (usize, usize)) -> [Option<(usize, usize)>; 5] {
-        let left = match self.field[y][x - 1] {
-            FieldType::Blizzards(a) if a.iter().all(|f| f.is_none()) => Some((x - 1, y)),
-            _ => None,
-        };
-        let right = match self.field[y][x + 1] {
-            FieldType::Blizzards(a) if a.iter().all(|f| f.is_none()) => Some((x + 1, y)),
-            _ => None,
-        };
-        let down = self.field.get(y + 1).and_then(|line| match line[x] {
-            FieldType::Blizzards(a) if a.iter().all(|f| f.is_none()) => Some((x, y + 1)),
-            _ => None,
-        });
-        let up = y
-            .checked_sub(1)
-            .and_then(|checked_y| match self.field[checked_y][x] {
-                FieldType::Blizzards(a) if a.iter().all(|f| f.is_none()) => Some((x, checked_y)),
-                _ => None,
-            });
-        let mid = match self.field[y][x] {
-            FieldType::Blizzards(a) if a.iter().all(|f| f.is_none()) => Some((x, y)),
-            _ => None,
-        };
-        [left, right, up, down, mid]
+}
+
+#[derive(Debug, Clone)]
+pub struct Arrivals {
+    width: usize,
+    steps: Vec<Vec<Option<u32>>>,
+}
+
+impl Arrivals {
+    pub fn earliest(&self, (x, y): (usize, usize), depart: usize) -> Option<usize> {
+        if x >= self.width {
+            return None;
+        }
+        let steps = self.steps[depart % self.steps.len()].get(y * self.width + x)?;
+        steps.map(|n| depart + n as usize)
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+    Wait,
+}
+
+impl Move {
+    fn between((x, y): (usize, usize), to: (usize, usize)) -> Move {
+        match to {
+            _ if to == (x, y) => Move::Wait,
+            (to_x, _) if to_x < x => Move::Left,
+            (to_x, _) if to_x > x => Move::Right,
+            (_, to_y) if to_y < y => Move::Up,
+            _ => Move::Down,
+        }
+    }
+
+    fn apply(self, (x, y): (usize, usize)) -> (usize, usize) {
+        match self {
+            Move::Up => (x, y - 1),
+            Move::Down => (x, y + 1),
+            Move::Left => (x - 1, y),
+            Move::Right => (x + 1, y),
+            Move::Wait => (x, y),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Up => write!(f, "move up"),
+            Move::Down => write!(f, "move down"),
+            Move::Left => write!(f, "move left"),
+            Move::Right => write!(f, "move right"),
+            Move::Wait => write!(f, "wait"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    field: Field,
+    from: (usize, usize),
+    depart: usize,
+    moves: Vec<Move>,
+}
+
+impl Replay {
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+}
+
+impl Display for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut field = self.field.clone();
+        for _ in 0..self.depart {
+            field.tick();
+        }
+        let mut pos = self.from;
+        writeln!(f, "Initial state:")?;
+        field.render(f, Some(pos))?;
+        for (minute, m) in (self.depart + 1..).zip(&self.moves) {
+            field.tick();
+            pos = m.apply(pos);
+            writeln!(f, "\nMinute {minute}, {m}:")?;
+            field.render(f, Some(pos))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum FieldType {
     Wall,
-    Blizzards([Option<Blizzard>; 4]),
+    Blizzards([u8; 8]),
+    Rock([u8; 8]),
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Blizzard {
+impl FieldType {
+    fn counts(&self) -> [u8; 8] {
+        match self {
+            FieldType::Blizzards(counts) | FieldType::Rock(counts) => *counts,
+            FieldType::Wall => [0; 8],
+        }
+    }
+
+    fn is_clear(&self) -> bool {
+        matches!(self, FieldType::Blizzards(counts) if counts.iter().all(|n| *n == 0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blizzard {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Blizzard {
+    const ALL: [Blizzard; 8] = [
+        Blizzard::Up,
+        Blizzard::Down,
+        Blizzard::Left,
+        Blizzard::Right,
+        Blizzard::UpLeft,
+        Blizzard::UpRight,
+        Blizzard::DownLeft,
+        Blizzard::DownRight,
+    ];
+
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Blizzard::Up => (0, -1),
+            Blizzard::Down => (0, 1),
+            Blizzard::Left => (-1, 0),
+            Blizzard::Right => (1, 0),
+            Blizzard::UpLeft => (-1, -1),
+            Blizzard::UpRight => (1, -1),
+            Blizzard::DownLeft => (-1, 1),
+            Blizzard::DownRight => (1, 1),
+        }
+    }
+
+    // diagonals use the keys around `wasd`: q e z c
+    fn glyph(&self) -> char {
+        match self {
+            Blizzard::Up => '^',
+            Blizzard::Down => 'v',
+            Blizzard::Left => '<',
+            Blizzard::Right => '>',
+            Blizzard::UpLeft => 'q',
+            Blizzard::UpRight => 'e',
+            Blizzard::DownLeft => 'z',
+            Blizzard::DownRight => 'c',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    NoOpening {
+        line: usize,
+    },
+    Stacked {
+        line: usize,
+        column: usize,
+    },
+    InvalidChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "no field found"),
+            ParseError::NoOpening { line } => write!(f, "{line}: no open cell to enter or leave by"),
+            ParseError::Stacked { line, column } => write!(
+                f,
+                "{line}:{column}: stacked blizzards don't keep their directions and can't be simulated"
+            ),
+            ParseError::InvalidChar {
+                line,
+                column,
+                found,
+            } => write!(f, "{line}:{column}: {found} found, not a valid character"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(input: &str) -> Field {
+    try_parse(input).unwrap()
 }
 
-fn parse(input: &str) -> Field {
+/// Parses the puzzle's format. A digit is rejected as [`ParseError::Stacked`], since it doesn't
+/// say which way its blizzards blow; only frames without stacked blizzards round-trip.
+pub fn try_parse(input: &str) -> Result<Field, ParseError> {
     let field = input
         .lines()
-        .map(|line| {
-            line.chars().fold(Vec::new(), |mut v, c| {
-                v.push(match c {
-                    '#' => FieldType::Wall,
-                    '.' => FieldType::Blizzards([None; 4]),
-                    '>' => FieldType::Blizzards([Some(Blizzard::Right), None, None, None]),
-                    '<' => FieldType::Blizzards([Some(Blizzard::Left), None, None, None]),
-                    '^' => FieldType::Blizzards([Some(Blizzard::Up), None, None, None]),
-                    'v' => FieldType::Blizzards([Some(Blizzard::Down), None, None, None]),
-                    rest => panic!("{rest} found, not a valid character"),
-                });
-                v
-            })
+        .enumerate()
+        .map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(x, c)| match c {
+                    '#' => Ok(FieldType::Wall),
+                    'O' => Ok(FieldType::Rock([0; 8])),
+                    '.' => Ok(FieldType::Blizzards([0; 8])),
+                    '2'..='9' => Err(ParseError::Stacked {
+                        line: y + 1,
+                        column: x + 1,
+                    }),
+                    c => match Blizzard::ALL.iter().position(|b| b.glyph() == c) {
+                        Some(i) => {
+                            let mut counts = [0; 8];
+                            counts[i] = 1;
+                            Ok(FieldType::Blizzards(counts))
+                        }
+                        None => Err(ParseError::InvalidChar {
+                            line: y + 1,
+                            column: x + 1,
+                            found: c,
+                        }),
+                    },
+                })
+                .collect::<Result<Vec<_>, _>>()
         })
-        .collect::<Vec<_>>();
-    let start = (
-        field
-            .first()
-            .and_then(|f| {
-                f.iter().position(
-                    |t| matches!(t, FieldType::Blizzards(a) if a.iter().all(|a| a.is_none())),
-                )
-            })
-            .unwrap(),
-        0,
-    );
-    let end = (
-        field
-            .last()
-            .and_then(|f| {
-                f.iter().position(
-                    |t| matches!(t, FieldType::Blizzards(a) if a.iter().all(|a| a.is_none())),
-                )
-            })
-            .unwrap(),
-        field.len() - 1,
-    );
-    let width = field[0].len();
-    Field {
+        .collect::<Result<Vec<_>, _>>()?;
+    let (first, last) = match (field.first(), field.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(ParseError::Empty),
+    };
+    let opening = |line: &[FieldType], y: usize| {
+        line.iter()
+            .position(FieldType::is_clear)
+            .map(|x| (x, y))
+            .ok_or(ParseError::NoOpening { line: y + 1 })
+    };
+    let start = opening(first, 0)?;
+    let end = opening(last, field.len() - 1)?;
+    let width = first.len();
+    Ok(Field {
         field,
         start,
         end,
         width,
-    }
+    })
 }
 
-fn find_path(mut field: Field) -> usize {
-    let mut positions = HashSet::from([field.start]);
-
-    let mut minutes = 0;
-    loop {
-        positions = positions.into_iter().fold(HashSet::new(), |mut set, pos| {
-            set.extend(field.new_pos(pos).into_iter().flatten());
-            set
-        });
-        if positions.contains(&field.end) {
-            return minutes;
-        }
-        field.tick();
-        minutes += 1;
-    }
+fn find_path(field: &Field) -> Option<usize> {
+    Valley::new(field).earliest(field.start, field.end, 0)
 }
 
-fn find_paths(mut field: Field) -> usize {
-    let mut positions = HashSet::from([field.start]);
+pub fn find_route(field: &Field) -> Option<Replay> {
+    let moves = Valley::new(field).route(field.start, field.end, 0)?;
+    Some(Replay {
+        field: field.clone(),
+        from: field.start,
+        depart: 0,
+        moves,
+    })
+}
 
-    let mut minutes = 0;
-    let first = loop {
-        positions = positions.into_iter().fold(HashSet::new(), |mut set, pos| {
-            set.extend(field.new_pos(pos).into_iter().flatten());
-            set
-        });
-        if positions.contains(&field.end) {
-            break minutes;
-        }
-        field.tick();
-        minutes += 1;
-    };
-    let mut minutes = 0;
-    let mut positions = HashSet::from([field.end]);
-    let back = loop {
-        positions = positions.into_iter().fold(HashSet::new(), |mut set, pos| {
-            set.extend(field.new_pos(pos).into_iter().flatten());
-            set
-        });
-        if positions.contains(&field.start) {
-            break minutes;
-        }
-        field.tick();
-        minutes += 1;
-    };
-    let mut positions = HashSet::from([field.start]);
-    let mut minutes = 0;
-    let back_again = loop {
-        positions = positions.into_iter().fold(HashSet::new(), |mut set, pos| {
-            set.extend(field.new_pos(pos).into_iter().flatten());
-            set
-        });
-        if positions.contains(&field.end) {
-            break minutes;
-        }
-        field.tick();
-        minutes += 1;
-    };
-    first + back + back_again
+fn supply_run(field: &Field, trips: usize) -> Option<usize> {
+    let waypoints: Vec<_> = [field.end, field.start]
+        .into_iter()
+        .cycle()
+        .take(trips)
+        .collect();
+    Valley::new(field)
+        .itinerary(field.start, &waypoints, 0)?
+        .last()
+        .copied()
 }
 
 pub fn process1(input: &str) -> usize {
     let field = parse(input);
-    find_path(field)
+    find_path(&field).unwrap()
 }
 
 pub fn process2(input: &str) -> usize {
     let field = parse(input);
-    find_paths(field)
+    supply_run(&field, 3).unwrap()
 }
 
 #[cfg(test)]
@@ -319,7 +640,6 @@ mod tests {
     }
 
     #[test]
-    
     fn process_two_test() {
         let input = "#.######
 #>>.<^<#
@@ -330,4 +650,315 @@ mod tests {
         let result = process2(input);
         assert_eq!(result, 54)
     }
+
+    #[test]
+    fn valley_period_test() {
+        let input = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
+        let mut field = parse(input);
+        let valley = Valley::new(&field);
+        assert_eq!(valley.period(), 12);
+        for t in 0..valley.period() {
+            for (y, line) in field.field.iter().enumerate() {
+                for (x, cell) in line.iter().enumerate() {
+                    let open = cell.is_clear();
+                    assert_eq!(valley.is_open((x, y), t), open);
+                }
+            }
+            field.tick();
+        }
+    }
+
+    #[test]
+    fn unreachable_test() {
+        let input = "#.#
+#v#
+#^#
+#.#";
+        assert_eq!(find_path(&parse(input)), None);
+    }
+
+    #[test]
+    fn itinerary_test() {
+        let input = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
+        let field = parse(input);
+        let valley = Valley::new(&field);
+        assert_eq!(
+            valley.itinerary(field.start, &[field.end, field.start, field.end], 0),
+            Some(vec![18, 41, 54])
+        );
+        assert_eq!(
+            valley.itinerary(field.end, &[field.start], 18),
+            Some(vec![41])
+        );
+        assert_eq!(supply_run(&field, 1), find_path(&field));
+        assert_eq!(valley.itinerary(field.start, &[], 7), Some(vec![]));
+    }
+
+    #[test]
+    fn route_test() {
+        let input = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
+        let field = parse(input);
+        let replay = find_route(&field).unwrap();
+        assert_eq!(replay.moves().len(), 18);
+        let valley = Valley::new(&field);
+        let mut pos = field.start;
+        for (time, m) in replay.moves().iter().enumerate() {
+            pos = m.apply(pos);
+            assert!(valley.is_open(pos, time + 1));
+        }
+        assert_eq!(pos, field.end);
+
+        let frames = replay.to_string();
+        assert!(frames.starts_with(
+            "Initial state:
+#E######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+
+Minute 1, "
+        ));
+        assert!(frames.contains(
+            "#.>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+"
+        ));
+        assert!(frames.ends_with(
+            "Minute 18, move down:
+#.######
+#>2.<.<#
+#.2v^2<#
+#>..>2>#
+#<....>#
+######E#
+"
+        ));
+    }
+
+    fn random_field(seed: u64, width: usize, height: usize) -> Field {
+        let mut state = seed;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut input = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                input.push(match (x, y) {
+                    (1, 0) => '.',
+                    (x, y) if x == width - 2 && y == height - 1 => '.',
+                    (x, y) if x == 0 || y == 0 || x == width - 1 || y == height - 1 => '#',
+                    _ => ['.', '.', '>', '<', '^', 'v'][next() as usize % 6],
+                });
+            }
+            input.push('\n');
+        }
+        parse(&input)
+    }
+
+    #[test]
+    fn display_round_trip_test() {
+        let input = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+";
+        let mut field = parse(input);
+        assert_eq!(field.to_string(), input);
+        field.tick();
+        assert_eq!(
+            field.to_string(),
+            "#.######
+#.>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+"
+        );
+    }
+
+    #[test]
+    fn display_round_trip_property() {
+        let (mut compared, mut stacked) = (0, 0);
+        for seed in 1..=50 {
+            let (width, height) = (3 + seed as usize % 7, 3 + seed as usize * 7 % 5);
+            let mut field = random_field(seed * 0x9e37_79b9, width, height);
+            let period = Valley::new(&field).period();
+            for _ in 0..=period {
+                let text = field.to_string();
+                if text.contains(|c: char| c.is_ascii_digit()) {
+                    let Err(ParseError::Stacked { line, column }) = try_parse(&text) else {
+                        panic!("{text} parsed despite stacked blizzards");
+                    };
+                    let n = field.field[line - 1][column - 1]
+                        .counts()
+                        .iter()
+                        .sum::<u8>();
+                    let c = text.lines().nth(line - 1).unwrap().chars().nth(column - 1);
+                    assert!(n > 1);
+                    assert_eq!(c, char::from_digit(n.min(9) as u32, 10));
+                    let mut later = field.clone();
+                    (0..period).for_each(|_| later.tick());
+                    assert_eq!(later.to_string(), text);
+                    stacked += 1;
+                } else {
+                    let mut reparsed = try_parse(&text).unwrap();
+                    assert_eq!(find_path(&reparsed), find_path(&field));
+                    let mut original = field.clone();
+                    for _ in 0..=period {
+                        assert_eq!(reparsed, original);
+                        reparsed.tick();
+                        original.tick();
+                    }
+                    compared += 1;
+                }
+                field.tick();
+            }
+        }
+        assert!(compared >= 100, "only {compared} frames compared");
+        assert!(stacked >= 100, "only {stacked} stacked frames");
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(try_parse(""), Err(ParseError::Empty));
+        assert_eq!(
+            try_parse("###\n#.#\n#.#"),
+            Err(ParseError::NoOpening { line: 1 })
+        );
+        assert_eq!(
+            try_parse("#.#\n#.#\n#>#"),
+            Err(ParseError::NoOpening { line: 3 })
+        );
+        assert_eq!(
+            try_parse("#.#\n#x#\n#.#"),
+            Err(ParseError::InvalidChar {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn arrivals_test() {
+        let input = "#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#";
+        let field = parse(input);
+        let valley = Valley::new(&field);
+        let to_end = valley.arrivals(field.end());
+        assert_eq!(to_end.earliest(field.start(), 0), Some(18));
+        assert_eq!(
+            valley.arrivals(field.start()).earliest(field.end(), 18),
+            Some(41)
+        );
+        assert_eq!(to_end.earliest((9, 0), 0), None);
+        assert!(!valley.is_open((1, 1), 0));
+        assert_eq!(valley.earliest((1, 1), field.end(), 0), None);
+
+        for to in [field.end(), field.start(), (3, 2), (6, 4)] {
+            let arrivals = valley.arrivals(to);
+            for y in 0..valley.height {
+                for x in 0..valley.width {
+                    for depart in 0..valley.period() {
+                        assert_eq!(
+                            arrivals.earliest((x, y), depart),
+                            valley.earliest((x, y), to, depart)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extended_blizzards_test() {
+        let input = "#.#####
+#.....#
+#.c.O.#
+#..q..#
+#.....#
+#####.#
+";
+        let mut initial = parse(input);
+        initial.add_blizzard((1, 1), Blizzard::Right);
+        initial.add_blizzard((1, 1), Blizzard::Right);
+        let mut field = initial.clone();
+        let valley = Valley::new(&field);
+        assert_eq!(valley.period(), 20);
+        for t in 0..valley.period() {
+            for (y, line) in field.field.iter().enumerate() {
+                for (x, cell) in line.iter().enumerate() {
+                    assert_eq!(valley.is_open((x, y), t), cell.is_clear());
+                }
+            }
+            field.tick();
+        }
+        assert_eq!(field.field[1][1], {
+            let mut counts = [0; 8];
+            counts[3] = 2;
+            FieldType::Blizzards(counts)
+        });
+        assert!(field
+            .to_string()
+            .starts_with("#.#####\n#2....#\n#.c.O.#\n#..q..#\n"));
+
+        field.tick();
+        assert_eq!(
+            field.to_string(),
+            "#.#####\n#.2...#\n#.q.O.#\n#..c..#\n#.....#\n#####.#\n"
+        );
+        assert_eq!(
+            try_parse(&initial.to_string()),
+            Err(ParseError::Stacked { line: 2, column: 2 })
+        );
+        let mut rock = parse("#.###\n#>O.#\n###.#\n");
+        rock.tick();
+        assert_eq!(rock.to_string(), "#.###\n#.O.#\n###.#\n");
+        assert_eq!(rock.field[1][2], {
+            let mut counts = [0; 8];
+            counts[3] = 1;
+            FieldType::Rock(counts)
+        });
+        rock.tick();
+        assert_eq!(rock.to_string(), "#.###\n#.O>#\n###.#\n");
+
+        let route = find_route(&initial).unwrap();
+        let mut pos = field.start();
+        for (time, m) in route.moves().iter().enumerate() {
+            pos = m.apply(pos);
+            assert!(valley.is_open(pos, time + 1));
+            assert_ne!(pos, (4, 2));
+        }
+        assert_eq!(pos, field.end());
+    }
 }
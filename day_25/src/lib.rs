@@ -1,67 +1,480 @@
-fn sum(input: &str) -> i64 {
-    input
-        .lines()
-        .map(|line| {
-            line.chars().rev().enumerate().fold(0i64, |acc, (i, c)| {
-                let i = i as u32;
-                match c {
-                    '=' => acc + -2 * 5i64.pow(i),
-                    '-' => acc + -1 * 5i64.pow(i),
-                    '0' => acc + 0 * 5i64.pow(i),
-                    '1' => acc + 1 * 5i64.pow(i),
-                    '2' => acc + 2 * 5i64.pow(i),
-                    rest => panic!("{rest} is not a valid char"),
-                }
-            })
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::iter::Sum;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetError {
+    EvenBase(usize),
+    TooSmall(usize),
+    TooLarge(usize),
+    Duplicate(char),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetError::EvenBase(base) => write!(f, "base {base} is not odd"),
+            AlphabetError::TooSmall(base) => write!(f, "base {base} is smaller than 3"),
+            AlphabetError::TooLarge(base) => write!(f, "base {base} is larger than 255"),
+            AlphabetError::Duplicate(c) => write!(f, "digit {c:?} appears more than once"),
+        }
+    }
+}
+
+impl std::error::Error for AlphabetError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    InvalidDigit { index: usize, found: char },
+    Overflow,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty number"),
+            ParseError::InvalidDigit { index, found } => {
+                write!(f, "{found:?} at column {} is not a valid digit", index + 1)
+            }
+            ParseError::Overflow => write!(f, "number does not fit in an i128"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalancedBase<'a> {
+    digits: &'a [char],
+}
+
+pub const SNAFU: BalancedBase<'static> = BalancedBase {
+    digits: &['=', '-', '0', '1', '2'],
+};
+
+pub const BALANCED_TERNARY: BalancedBase<'static> = BalancedBase {
+    digits: &['-', '0', '+'],
+};
+
+impl<'a> BalancedBase<'a> {
+    pub fn new(digits: &'a [char]) -> Result<Self, AlphabetError> {
+        if digits.len().is_multiple_of(2) {
+            return Err(AlphabetError::EvenBase(digits.len()));
+        }
+        if digits.len() < 3 {
+            return Err(AlphabetError::TooSmall(digits.len()));
+        }
+        if digits.len() > 255 {
+            return Err(AlphabetError::TooLarge(digits.len()));
+        }
+        if let Some(i) = (1..digits.len()).find(|&i| digits[..i].contains(&digits[i])) {
+            return Err(AlphabetError::Duplicate(digits[i]));
+        }
+        Ok(BalancedBase { digits })
+    }
+
+    pub fn base(&self) -> i64 {
+        self.digits.len() as i64
+    }
+
+    fn half(&self) -> i64 {
+        self.base() / 2
+    }
+
+    pub fn encode(&self, num: i128) -> String {
+        self.format(&self.digits_of(num))
+    }
+
+    pub fn decode(&self, s: &str) -> Result<i128, ParseError> {
+        self.value_of(&self.parse(s)?).ok_or(ParseError::Overflow)
+    }
+
+    pub fn add(&self, a: &str, b: &str) -> Result<String, ParseError> {
+        let (a, b) = (self.parse(a)?, self.parse(b)?);
+        Ok(self.format(&self.add_digits(&a, &b)))
+    }
+
+    fn parse(&self, s: &str) -> Result<Vec<i8>, ParseError> {
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let digits = s
+            .char_indices()
+            .rev()
+            .map(
+                |(index, found)| match self.digits.iter().position(|&c| c == found) {
+                    Some(d) => Ok(d as i64 - self.half()),
+                    None => Err(ParseError::InvalidDigit { index, found }),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.normalize(digits))
+    }
+
+    fn format(&self, digits: &[i8]) -> String {
+        if digits.is_empty() {
+            return self.digits[self.half() as usize].to_string();
+        }
+        digits
+            .iter()
+            .rev()
+            .map(|&d| self.digits[(d as i64 + self.half()) as usize])
+            .collect()
+    }
+
+    fn add_digits(&self, a: &[i8], b: &[i8]) -> Vec<i8> {
+        let len = a.len().max(b.len());
+        self.normalize((0..len).map(|i| {
+            let a = a.get(i).copied().unwrap_or(0);
+            let b = b.get(i).copied().unwrap_or(0);
+            a as i64 + b as i64
+        }))
+    }
+
+    fn digits_of(&self, mut num: i128) -> Vec<i8> {
+        let base = self.base() as i128;
+        let mut digits = vec![];
+        while num != 0 {
+            let mut digit = num.rem_euclid(base);
+            num = num.div_euclid(base);
+            if digit > base / 2 {
+                digit -= base;
+                num += 1;
+            }
+            digits.push(digit as i8);
+        }
+        digits
+    }
+
+    fn value_of(&self, digits: &[i8]) -> Option<i128> {
+        digits.iter().rev().try_fold(0i128, |acc, &d| {
+            acc.checked_mul(self.base() as i128)?.checked_add(d as i128)
         })
-        .sum()
+    }
+
+    fn normalize(&self, values: impl IntoIterator<Item = i64>) -> Vec<i8> {
+        let base = self.base();
+        let mut digits = vec![];
+        let mut carry = 0;
+        let mut values = values.into_iter();
+        loop {
+            let value = match values.next() {
+                Some(v) => v + carry,
+                None if carry != 0 => carry,
+                None => break,
+            };
+            let mut digit = value.rem_euclid(base);
+            if digit > base / 2 {
+                digit -= base;
+            }
+            carry = (value - digit) / base;
+            digits.push(digit as i8);
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        digits
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Snafu {
+    digits: Vec<i8>,
 }
 
-fn snafu_output(num: i64) -> String {
-    let mut num = num;
-    let s = std::iter::from_fn(move || {
-        if num == 0 {
-            None
+impl Snafu {
+    pub fn signum(&self) -> i8 {
+        self.digits.last().map_or(0, |d| d.signum())
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        SNAFU.value_of(&self.digits)
+    }
+
+    pub fn from_decimal(s: &str) -> Result<Snafu, ParseError> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let offset = s.len() - digits.len();
+        let ten = Snafu::from(10);
+        let value = digits
+            .char_indices()
+            .try_fold(Snafu::default(), |acc, (i, c)| match c.to_digit(10) {
+                Some(d) => Ok(&(&acc * &ten) + &Snafu::from(d as i128)),
+                None => Err(ParseError::InvalidDigit {
+                    index: offset + i,
+                    found: c,
+                }),
+            })?;
+        Ok(if negative { -value } else { value })
+    }
+
+    pub fn to_decimal(&self) -> String {
+        let magnitude = if self.signum() < 0 {
+            -self
         } else {
-            match num % 5 {
-                0 => {
-                    num /= 5;
-                    Some('0')
-                }
-                1 => {
-                    num -= 1;
-                    num /= 5;
-                    Some('1')
-                }
-                2 => {
-                    num -= 2;
-                    num /= 5;
-                    Some('2')
-                }
-                3 => {
-                    num += 2;
-                    num /= 5;
-                    Some('=')
-                }
-                4 => {
-                    num += 1;
-                    num /= 5;
-                    Some('-')
-                }
-                _ => panic!(),
+            self.clone()
+        };
+        let mut decimal: Vec<i64> = vec![];
+        for &d in magnitude.digits.iter().rev() {
+            let mut carry = d as i64;
+            for digit in decimal.iter_mut() {
+                let value = *digit * SNAFU.base() + carry;
+                *digit = value.rem_euclid(10);
+                carry = value.div_euclid(10);
+            }
+            while carry > 0 {
+                decimal.push(carry % 10);
+                carry /= 10;
             }
         }
-    })
-    .collect::<String>();
-    s.chars().rev().collect()
+        let sign = if self.signum() < 0 { "-" } else { "" };
+        let digits: String = decimal
+            .iter()
+            .rev()
+            .map(|&d| char::from_digit(d as u32, 10).unwrap())
+            .collect();
+        match digits.is_empty() {
+            true => "0".to_string(),
+            false => format!("{sign}{digits}"),
+        }
+    }
+}
+
+impl From<i128> for Snafu {
+    fn from(num: i128) -> Self {
+        Snafu {
+            digits: SNAFU.digits_of(num),
+        }
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Snafu {
+            digits: SNAFU.parse(s)?,
+        })
+    }
+}
+
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&SNAFU.format(&self.digits))
+    }
+}
+
+impl PartialOrd for Snafu {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Snafu {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self - other).signum().cmp(&0)
+    }
+}
+
+impl Add for &Snafu {
+    type Output = Snafu;
+
+    fn add(self, rhs: &Snafu) -> Snafu {
+        Snafu {
+            digits: SNAFU.add_digits(&self.digits, &rhs.digits),
+        }
+    }
+}
+
+impl Neg for &Snafu {
+    type Output = Snafu;
+
+    fn neg(self) -> Snafu {
+        Snafu {
+            digits: self.digits.iter().map(|d| -d).collect(),
+        }
+    }
+}
+
+impl Sub for &Snafu {
+    type Output = Snafu;
+
+    fn sub(self, rhs: &Snafu) -> Snafu {
+        self + &-rhs
+    }
+}
+
+impl Mul for &Snafu {
+    type Output = Snafu;
+
+    fn mul(self, rhs: &Snafu) -> Snafu {
+        let mut products = vec![0i64; self.digits.len() + rhs.digits.len()];
+        for (i, &a) in self.digits.iter().enumerate() {
+            for (j, &b) in rhs.digits.iter().enumerate() {
+                products[i + j] += (a * b) as i64;
+            }
+        }
+        Snafu {
+            digits: SNAFU.normalize(products),
+        }
+    }
+}
+
+macro_rules! forward_binop {
+    ($($trait:ident $method:ident),*) => {$(
+        impl $trait for Snafu {
+            type Output = Snafu;
+
+            fn $method(self, rhs: Snafu) -> Snafu {
+                (&self).$method(&rhs)
+            }
+        }
+    )*};
+}
+
+forward_binop!(Add add, Sub sub, Mul mul);
+
+impl Neg for Snafu {
+    type Output = Snafu;
+
+    fn neg(self) -> Snafu {
+        -&self
+    }
+}
+
+impl Sum for Snafu {
+    fn sum<I: Iterator<Item = Snafu>>(iter: I) -> Snafu {
+        iter.fold(Snafu::default(), |acc, n| &acc + &n)
+    }
+}
+
+impl<'a> Sum<&'a Snafu> for Snafu {
+    fn sum<I: Iterator<Item = &'a Snafu>>(iter: I) -> Snafu {
+        iter.fold(Snafu::default(), |acc, n| &acc + n)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    ToSnafu,
+    ToDecimal,
+}
+
+impl Direction {
+    // goes with whichever kind of line is in the majority, so a few mistyped lines
+    // are reported as errors rather than making the whole stream ambiguous
+    pub fn detect<S: AsRef<str>>(lines: &[S]) -> Option<Direction> {
+        let (mut decimal, mut snafu) = (0, 0);
+        for line in lines.iter().map(|line| line.as_ref().trim()) {
+            let is_decimal = line.contains(|c| ('3'..='9').contains(&c)) || line.starts_with('+');
+            let is_snafu = line.contains('=')
+                || line == "-"
+                || line.get(1..).is_some_and(|rest| rest.contains('-'));
+            match (is_decimal, is_snafu) {
+                (true, false) => decimal += 1,
+                (false, true) => snafu += 1,
+                _ => {}
+            }
+        }
+        match decimal.cmp(&snafu) {
+            Ordering::Greater => Some(Direction::ToSnafu),
+            Ordering::Less => Some(Direction::ToDecimal),
+            Ordering::Equal => None,
+        }
+    }
+
+    fn convert(self, line: &str) -> Result<String, ParseError> {
+        match self {
+            Direction::ToSnafu => Snafu::from_decimal(line).map(|n| n.to_string()),
+            Direction::ToDecimal => line.parse::<Snafu>().map(|n| n.to_decimal()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub error: ParseError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for LineError {}
+
+#[derive(Debug)]
+pub enum ConvertError {
+    Io(io::Error),
+    Ambiguous,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Io(e) => write!(f, "{e}"),
+            ConvertError::Ambiguous => {
+                write!(f, "can't tell decimal from SNAFU input, give a direction")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl From<io::Error> for ConvertError {
+    fn from(e: io::Error) -> Self {
+        ConvertError::Io(e)
+    }
+}
+
+// blank and unconvertible lines are left out of the output; errors carry input line numbers
+pub fn convert(
+    reader: impl BufRead,
+    mut writer: impl Write,
+    direction: Option<Direction>,
+) -> Result<Vec<LineError>, ConvertError> {
+    let lines = reader.lines().collect::<io::Result<Vec<_>>>()?;
+    let direction = direction
+        .or_else(|| Direction::detect(&lines))
+        .ok_or(ConvertError::Ambiguous)?;
+    let mut errors = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match direction.convert(line) {
+            Ok(converted) => writeln!(writer, "{converted}")?,
+            Err(error) => errors.push(LineError { line: i + 1, error }),
+        }
+    }
+    Ok(errors)
+}
+
+fn sum(input: &str) -> Snafu {
+    input
+        .lines()
+        .map(|line| line.parse::<Snafu>().unwrap())
+        .sum()
 }
 
 pub fn process1(input: &str) -> String {
-    let num = sum(input);
-    snafu_output(num)
+    sum(input).to_string()
 }
 
-pub fn process2(input: &str) -> String {
+pub fn process2(_input: &str) -> String {
     todo!()
 }
 
@@ -99,7 +512,7 @@ mod tests {
 1=
 122";
         let result = sum(input);
-        assert_eq!(result, 4890)
+        assert_eq!(result.to_i128(), Some(4890))
     }
 
     #[test]
@@ -139,4 +552,159 @@ mod tests {
         let result = process2(input);
         assert_eq!(result, "20")
     }
+
+    #[test]
+    fn snafu_test() {
+        let pairs = [
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (8, "2="),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+        ];
+        for (num, s) in pairs {
+            assert_eq!(Snafu::from(num).to_string(), s);
+            assert_eq!(s.parse::<Snafu>().unwrap().to_i128(), Some(num));
+            assert_eq!((-Snafu::from(num)).to_i128(), Some(-num));
+        }
+        assert_eq!(Snafu::from(0).to_string(), "0");
+        assert_eq!("00".parse::<Snafu>().unwrap(), Snafu::default());
+        assert_eq!(
+            "1=x".parse::<Snafu>(),
+            Err(ParseError::InvalidDigit {
+                index: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!("".parse::<Snafu>(), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        for a in -60..60 {
+            for b in -60..60 {
+                let (x, y) = (Snafu::from(a), Snafu::from(b));
+                assert_eq!((&x + &y).to_i128(), Some(a + b));
+                assert_eq!((&x - &y).to_i128(), Some(a - b));
+                assert_eq!((&x * &y).to_i128(), Some(a * b));
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn unbounded_test() {
+        let big =
+            Snafu::from_decimal("-123456789012345678901234567890123456789012345678901234567890")
+                .unwrap();
+        assert_eq!(big.to_i128(), None);
+        assert_eq!(
+            big.to_decimal(),
+            "-123456789012345678901234567890123456789012345678901234567890"
+        );
+        assert_eq!(big.to_string().parse::<Snafu>().unwrap(), big);
+        let square = &big * &big;
+        assert_eq!((&square - &square).to_decimal(), "0");
+        assert_eq!(
+            Snafu::from_decimal(&i128::MAX.to_string())
+                .unwrap()
+                .to_i128(),
+            Some(i128::MAX)
+        );
+        assert_eq!(Snafu::from(i128::MIN).to_decimal(), i128::MIN.to_string());
+        assert_eq!(
+            Snafu::from_decimal("12a"),
+            Err(ParseError::InvalidDigit {
+                index: 2,
+                found: 'a'
+            })
+        );
+    }
+
+    #[test]
+    fn balanced_base_test() {
+        assert_eq!(BALANCED_TERNARY.encode(0), "0");
+        assert_eq!(BALANCED_TERNARY.encode(8), "+0-");
+        assert_eq!(BALANCED_TERNARY.encode(-8), "-0+");
+        assert_eq!(BALANCED_TERNARY.decode("+--+"), Ok(16));
+        assert_eq!(BALANCED_TERNARY.add("+0-", "+--+"), Ok("+0-0".to_string()));
+        assert_eq!(SNAFU.add("1=-0-2", "12111"), Ok(SNAFU.encode(1747 + 906)));
+        assert_eq!(
+            BALANCED_TERNARY.decode("+0x"),
+            Err(ParseError::InvalidDigit {
+                index: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            BALANCED_TERNARY.decode(&"+".repeat(90)),
+            Err(ParseError::Overflow)
+        );
+
+        let nonary = ['d', 'c', 'b', 'a', '0', 'A', 'B', 'C', 'D'];
+        let nonary = BalancedBase::new(&nonary).unwrap();
+        for n in -500..500 {
+            assert_eq!(nonary.decode(&nonary.encode(n)), Ok(n));
+        }
+        assert_eq!(BalancedBase::new(&['0']), Err(AlphabetError::TooSmall(1)));
+        assert_eq!(BalancedBase::new(&[]), Err(AlphabetError::EvenBase(0)));
+        assert_eq!(
+            BalancedBase::new(&['0', '1']),
+            Err(AlphabetError::EvenBase(2))
+        );
+        assert_eq!(
+            BalancedBase::new(&['-', '0', '-']),
+            Err(AlphabetError::Duplicate('-'))
+        );
+    }
+
+    #[test]
+    fn convert_test() {
+        let mut output = vec![];
+        let errors = convert("1=-0-2\n\n12111\n2=x\n-\n".as_bytes(), &mut output, None).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1747\n906\n-1\n");
+        assert_eq!(
+            errors,
+            [LineError {
+                line: 4,
+                error: ParseError::InvalidDigit {
+                    index: 2,
+                    found: 'x'
+                }
+            }]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "line 4: 'x' at column 3 is not a valid digit"
+        );
+
+        let mut output = vec![];
+        let errors = convert("2022\n-3\n1.5\n".as_bytes(), &mut output, None).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1=11-2\n-2\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+
+        let mut output = vec![];
+        let errors = convert("10\n-2\n".as_bytes(), &mut output, Some(Direction::ToSnafu)).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(String::from_utf8(output).unwrap(), "20\n=\n");
+
+        let mut output = vec![];
+        let result = convert("10\n21\n".as_bytes(), &mut output, None);
+        assert!(matches!(result, Err(ConvertError::Ambiguous)));
+        assert!(output.is_empty());
+        assert_eq!(Direction::detect(&["12", "1-"]), Some(Direction::ToDecimal));
+        assert_eq!(Direction::detect(&["12", "-7"]), Some(Direction::ToSnafu));
+        assert_eq!(Direction::detect(&["2=", "7"]), None);
+        assert_eq!(Direction::detect(&["3=", "12"]), None);
+
+        let mut output = vec![];
+        let errors = convert("2022\n15\n2=\n36\n1-1\n7\n".as_bytes(), &mut output, None).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1=11-2\n1=0\n121\n12\n");
+        assert_eq!(errors.iter().map(|e| e.line).collect::<Vec<_>>(), [3, 5]);
+    }
 }